@@ -9,7 +9,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut image = Image::new();
 
-    let circle = Components::Circle(config.width / 2, config.height / 2, 10, RED);
+    let circle = Components::Circle(
+        config.width as f32 / 2.0,
+        config.height as f32 / 2.0,
+        10.0,
+        RED,
+    );
 
     image.config(config).init()?.add_component(&circle).draw()?;
     Ok(())
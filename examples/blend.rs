@@ -9,18 +9,23 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut image = Image::new();
 
-    let circle1 = Components::Circle(config.width / 2, config.height / 2, 350, RED);
+    let circle1 = Components::Circle(
+        config.width as f32 / 2.0,
+        config.height as f32 / 2.0,
+        350.0,
+        RED,
+    );
     let circle2 = Components::Circle(
-        config.width / 2,
-        config.height / 2,
-        300,
+        config.width as f32 / 2.0,
+        config.height as f32 / 2.0,
+        300.0,
         Rgba([255, 0, 255, 120]),
     );
     let rectangle = Components::Rectangle(
-        100,
-        100,
-        config.width / 2 - 50,
-        config.height / 2 - 50,
+        100.0,
+        100.0,
+        config.width as f32 / 2.0 - 50.0,
+        config.height as f32 / 2.0 - 50.0,
         Rgba([120, 0, 255, 19]),
     );
 
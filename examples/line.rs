@@ -16,14 +16,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut image = Image::new();
 
-    let line1 = Components::Line(0, 0, WIDTH, HEIGHT, GREEN);
-    let line2 = Components::Line(WIDTH, 0, 0, HEIGHT, GREEN);
-    let circle = Components::Circle(WIDTH / 2, HEIGHT / 2, 100, Rgba([0, 255, 0, 150]));
+    let line1 = Components::Line(0.0, 0.0, WIDTH as f32, HEIGHT as f32, GREEN);
+    let line2 = Components::Line(WIDTH as f32, 0.0, 0.0, HEIGHT as f32, GREEN);
+    let circle = Components::Circle(
+        WIDTH as f32 / 2.0,
+        HEIGHT as f32 / 2.0,
+        100.0,
+        Rgba([0, 255, 0, 150]),
+    );
     let text = Components::Text(
-        WIDTH / 2 - 210,
-        HEIGHT / 2 - 250,
+        WIDTH as f32 / 2.0 - 135.0,
+        HEIGHT as f32 / 2.0 - 250.0,
         40,
-        "Xiaolin Wu's Line Algorithm",
+        "Anti-aliased Lines",
         BLACK,
         Some((GREEN, 3)),
     );
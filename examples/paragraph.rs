@@ -0,0 +1,36 @@
+use omage::colors::*;
+use omage::text::TextStyle;
+use omage::{Components, Config, Image};
+
+const HEIGHT: u32 = 300;
+const WIDTH: u32 = 400;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::new(
+        WIDTH,
+        HEIGHT,
+        WHITE,
+        Some(BLACK),
+        "output.png",
+        Some("./fonts/Roboto-Medium.ttf"),
+    );
+
+    let mut image = Image::new();
+
+    let style = TextStyle {
+        max_width: Some(WIDTH as f32 - 40.0),
+        line_height: 1.3,
+        ..Default::default()
+    };
+    let text = Components::StyledText(
+        20.0,
+        20.0,
+        28,
+        "omage\nA Rust library for drawing circles, rectangles, lines and text onto images.",
+        BLACK,
+        style,
+    );
+
+    image.config(config).init()?.add_component(&text).draw()?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use omage::colors::*;
+use omage::text::{HorizontalAlign, LineJoin, Outline, TextStyle, VerticalAlign};
 use omage::{Components, Config, Image, Rgba};
 
 const HEIGHT: u32 = 100;
@@ -16,18 +17,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut image = Image::new();
 
-    let circle1 = Components::Circle(50, 55, 30, Rgba([255, 0, 0, 200]));
-    let circle2 = Components::Circle(75, 55, 30, Rgba([0, 255, 0, 200]));
-    let circle3 = Components::Circle(65, 35, 30, Rgba([0, 0, 255, 200]));
+    let circle1 = Components::Circle(50.0, 55.0, 30.0, Rgba([255, 0, 0, 200]));
+    let circle2 = Components::Circle(75.0, 55.0, 30.0, Rgba([0, 255, 0, 200]));
+    let circle3 = Components::Circle(65.0, 35.0, 30.0, Rgba([0, 0, 255, 200]));
 
-    let text = "OMAGE";
-    let text = Components::Text(
-        config.width / 2 - 40,
-        config.height / 2 - 25,
+    let style = TextStyle {
+        outline: Some(Outline {
+            color: BLACK,
+            width: 3.0,
+            join: LineJoin::Round,
+        }),
+        align: HorizontalAlign::Center,
+        vertical_align: VerticalAlign::Middle,
+        ..Default::default()
+    };
+    let text = Components::StyledText(
+        config.width as f32 / 2.0,
+        config.height as f32 / 2.0,
         50,
-        text,
+        "OMAGE",
         Rgba([255, 255, 255, 255]),
-        Some((BLACK, 3)),
+        style,
     );
 
     image
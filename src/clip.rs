@@ -0,0 +1,166 @@
+use crate::mask::Mask;
+use crate::path::Path;
+use crate::transform::Transform;
+use rusttype::OutlineBuilder;
+
+/// A command building an arbitrary path, e.g. for a clip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Starts a new contour at the given point.
+    MoveTo(f32, f32),
+    /// Adds a straight line to the given point.
+    LineTo(f32, f32),
+    /// Adds a quadratic Bézier curve with one control point to the last point.
+    QuadTo((f32, f32), (f32, f32)),
+    /// Adds a cubic Bézier curve with two control points to the last point.
+    CubicTo((f32, f32), (f32, f32), (f32, f32)),
+    /// Closes the current contour with a straight line back to its first point.
+    Close,
+}
+
+/// A region that the drawing of a component is restricted to.
+///
+/// Clips are filled with anti-aliased edges, so pixels on the edge of a clip are drawn
+/// partially. Coordinates of a clip are in the coordinates of the component, and move along
+/// with its transform.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{Clip, Components, Rgba};
+///
+/// // Crop a group of components into a circle.
+/// let avatar = Components::Group(vec![
+///     Components::Rectangle(100.0, 100.0, 0.0, 0.0, Rgba([255, 180, 0, 255])),
+///     Components::Circle(50.0, 40.0, 20.0, Rgba([120, 60, 0, 255])),
+/// ])
+/// .clip(Clip::Circle { cx: 50.0, cy: 50.0, r: 50.0 });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clip {
+    /// A rectangle with its top-left corner at `(x, y)`.
+    Rectangle {
+        /// X-coordinate of the top-left corner of the rectangle.
+        x: f32,
+        /// Y-coordinate of the top-left corner of the rectangle.
+        y: f32,
+        /// Width of the rectangle.
+        width: f32,
+        /// Height of the rectangle.
+        height: f32,
+    },
+    /// A circle with its center at `(cx, cy)`.
+    Circle {
+        /// X-coordinate of the center of the circle.
+        cx: f32,
+        /// Y-coordinate of the center of the circle.
+        cy: f32,
+        /// Radius of the circle.
+        r: f32,
+    },
+    /// An arbitrary path, filled with the non-zero rule. Open contours are closed.
+    Path(Vec<PathCommand>),
+}
+
+impl Clip {
+    /// Rasterizes the clip through `transform` into a coverage mask of a canvas of the given
+    /// size.
+    ///
+    /// Returns `None` if the clip covers nothing on the canvas.
+    pub(crate) fn mask(&self, transform: &Transform, width: u32, height: u32) -> Option<Mask> {
+        let mut path = Path::new();
+        path.set_transform(*transform);
+        match *self {
+            Clip::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => path.add_rect(x, y, width, height),
+            Clip::Circle { cx, cy, r } => path.add_circle(cx, cy, r),
+            Clip::Path(ref commands) => {
+                for command in commands {
+                    match *command {
+                        PathCommand::MoveTo(x, y) => path.move_to(x, y),
+                        PathCommand::LineTo(x, y) => path.line_to(x, y),
+                        PathCommand::QuadTo((x1, y1), (x, y)) => path.quad_to(x1, y1, x, y),
+                        PathCommand::CubicTo((x1, y1), (x2, y2), (x, y)) => {
+                            path.curve_to(x1, y1, x2, y2, x, y)
+                        }
+                        PathCommand::Close => path.close(),
+                    }
+                }
+            }
+        }
+        path.rasterize(width, height, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{ComponentTrait, Components};
+    use crate::compositing::Canvas;
+    use crate::config::Config;
+    use image::Rgba;
+
+    /// Draws a red rectangle covering a blue 20×20 canvas, clipped by `clip`.
+    fn draw_clipped(clip: Clip, transform: Transform) -> Canvas {
+        let config = Config::new(20, 20, Rgba([0, 0, 0, 0]), None, "output.png", None);
+        let mut canvas = Canvas::from_pixel(20, 20, Rgba([0.0, 0.0, 1.0, 1.0]));
+        Components::Rectangle(20.0, 20.0, 0.0, 0.0, Rgba([255, 0, 0, 255]))
+            .clip(clip)
+            .transform(transform)
+            .draw(config, &mut canvas)
+            .unwrap();
+        canvas
+    }
+
+    #[test]
+    fn draws_only_inside_the_clip() {
+        let clip = Clip::Rectangle {
+            x: 5.5,
+            y: 5.0,
+            width: 10.0,
+            height: 10.0,
+        };
+        let canvas = draw_clipped(clip, Transform::identity());
+        assert_eq!(*canvas.get_pixel(10, 10), Rgba([1.0, 0.0, 0.0, 1.0]));
+        for (x, y) in [(2, 2), (10, 3), (17, 10), (10, 16)] {
+            assert_eq!(
+                *canvas.get_pixel(x, y),
+                Rgba([0.0, 0.0, 1.0, 1.0]),
+                "({x}, {y})"
+            );
+        }
+        // The pixel on the left edge is half inside the clip.
+        assert_eq!(*canvas.get_pixel(5, 10), Rgba([0.5, 0.0, 0.5, 1.0]));
+    }
+
+    #[test]
+    fn clips_move_along_with_the_component() {
+        let clip = Clip::Path(vec![
+            PathCommand::MoveTo(0.0, 0.0),
+            PathCommand::LineTo(5.0, 0.0),
+            PathCommand::LineTo(5.0, 5.0),
+            PathCommand::LineTo(0.0, 5.0),
+        ]);
+        let canvas = draw_clipped(clip, Transform::translate(10.0, 10.0));
+        assert_eq!(*canvas.get_pixel(12, 12), Rgba([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(*canvas.get_pixel(2, 2), Rgba([0.0, 0.0, 1.0, 1.0]));
+    }
+
+    #[test]
+    fn clips_off_the_canvas_cover_nothing() {
+        let clip = Clip::Circle {
+            cx: -50.0,
+            cy: -50.0,
+            r: 10.0,
+        };
+        assert!(clip.mask(&Transform::identity(), 20, 20).is_none());
+        let canvas = draw_clipped(clip, Transform::identity());
+        assert!(canvas
+            .pixels()
+            .all(|&pixel| pixel == Rgba([0.0, 0.0, 1.0, 1.0])));
+    }
+}
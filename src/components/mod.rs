@@ -1,9 +1,15 @@
 #![allow(non_snake_case)]
 
-use crate::config::Config;
+use crate::clip::Clip;
+use crate::compositing::{composite, BlendMode, Canvas, CompositeOp};
+use crate::config::{CanvasPolicy, Config};
 use crate::error::CustomError;
+use crate::path::Path;
+use crate::soft_mask::{MaskMode, SoftMask};
+use crate::text::{LineJoin, Outline, Span, TextStyle};
+use crate::transform::Transform;
 use circle::Circle;
-use image::{ImageBuffer, Rgba};
+use image::Rgba;
 use line::Line;
 use rectangle::Rectangle;
 use std::error::Error;
@@ -14,78 +20,107 @@ mod line;
 mod rectangle;
 mod text;
 
-/// A trait for drawing components on an image buffer.
+/// A trait for drawing components on an image canvas.
 pub trait ComponentTrait {
-    /// Draws the component on the image buffer using the specified configuration.
+    /// Draws the component on the canvas using the specified configuration.
     ///
     /// # Arguments
     ///
     /// * `config` - Configuration for the drawing canvas.
-    /// * `buffer` - Image buffer to draw the component on.
+    /// * `canvas` - Canvas to draw the component on.
     ///
     /// # Errors
     ///
     /// Returns an error if there is an issue drawing the component.
-    fn draw(
-        &self,
-        config: Config,
-        buffer: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
-    ) -> Result<(), Box<dyn Error>>;
+    fn draw(&self, config: Config, canvas: &mut Canvas) -> Result<(), Box<dyn Error>>;
+}
+
+/// A drawable component, created with the methods of `Components`.
+///
+/// Methods of the component change how it is combined with the canvas, and can be chained
+/// after creating it.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{Components, CompositeOp, Rgba};
+///
+/// let eraser = Components::Rectangle(20.0, 20.0, 10.0, 10.0, Rgba([0, 0, 0, 255]))
+///     .operator(CompositeOp::DestinationOut);
+/// ```
+pub struct Component {
+    /// What the component draws.
+    kind: ComponentKind,
+    /// Operator combining the component with the canvas.
+    operator: CompositeOp,
+    /// Mode mixing the colors of the component with the canvas.
+    blend_mode: BlendMode,
+    /// Opacity the component is drawn with, between `0.0` and `1.0`.
+    opacity: f32,
+    /// Transform from the coordinates of the component to the canvas.
+    transform: Transform,
+    /// Regions the drawing of the component is restricted to.
+    clips: Vec<Clip>,
+    /// Soft masks setting the opacity of the component.
+    masks: Vec<(SoftMask, MaskMode)>,
+    /// Position of the component in the drawing order, lower values being drawn first.
+    z_index: i32,
 }
 
 /// Enum representing different types of drawing components.
-pub enum Component {
+enum ComponentKind {
     /// Represents a circle component.
     Circle {
         /// X-coordinate of the circle's center.
-        cx: u32,
+        cx: f32,
         /// Y-coordinate of the circle's center.
-        cy: u32,
+        cy: f32,
         /// Radius of the circle.
-        r: u32,
+        r: f32,
         /// Color of the circle in Rgba format.
         color: Rgba<u8>,
     },
     /// Represents a rectangle component.
     Rectangle {
         /// Height of the rectangle.
-        h: u32,
+        h: f32,
         /// Width of the rectangle.
-        w: u32,
+        w: f32,
         /// X-coordinate of the top-left corner of the rectangle.
-        x: u32,
+        x: f32,
         /// Y-coordinate of the top-left corner of the rectangle.
-        y: u32,
+        y: f32,
         /// Color of the rectangle in Rgba format.
         color: Rgba<u8>,
     },
     /// Represents a line component.
     Line {
         /// X-coordinate of the starting point of the line.
-        x1: u32,
+        x1: f32,
         /// Y-coordinate of the starting point of the line.
-        y1: u32,
+        y1: f32,
         /// X-coordinate of the ending point of the line.
-        x2: u32,
+        x2: f32,
         /// Y-coordinate of the ending point of the line.
-        y2: u32,
+        y2: f32,
         /// Color of the line in Rgba format.
         color: Rgba<u8>,
     },
     /// Represents a text component.
     Text {
         /// X-coordinate of the top-left corner of the text.
-        x: u32,
+        x: f32,
         /// Y-coordinate of the top-left corner of the text.
-        y: u32,
-        /// Size of the text.
-        size: u32,
-        /// Text field of the text.
-        text: &'static str,
-        /// Color of the rectangle in Rgba format.
-        color: Rgba<u8>,
-        /// Border.
-        border: Option<(Rgba<u8>, u32)>,
+        y: f32,
+        /// Spans making up the content of the text.
+        spans: Vec<Span>,
+        /// Layout and styling options of the text.
+        style: TextStyle,
+    },
+    /// Represents a group of components drawn together.
+    Group {
+        /// Components of the group, drawn in order.
+        components: Vec<Component>,
     },
 }
 
@@ -95,22 +130,25 @@ pub enum Component {
 /// components in a 2D space. It offers methods for creating circles, rectangles, lines, and text
 /// components with specified attributes.
 ///
+/// Coordinates and sizes are in pixels and can be fractional, so components can be placed
+/// between pixels with smooth anti-aliased edges, e.g. for animations.
+///
 /// # Examples
 ///
 /// ```
 /// use omage::{Components, Rgba};
 ///
 /// // Create a new circle component
-/// let circle = Components::Circle(50, 50, 30, Rgba([255, 0, 0, 255]));
+/// let circle = Components::Circle(50.0, 50.0, 30.0, Rgba([255, 0, 0, 255]));
 ///
 /// // Create a new rectangle component
-/// let rectangle = Components::Rectangle(40, 60, 10, 20, Rgba([0, 255, 0, 255]));
+/// let rectangle = Components::Rectangle(40.0, 60.0, 10.0, 20.0, Rgba([0, 255, 0, 255]));
 ///
 /// // Create a new line component
-/// let line = Components::Line(10, 10, 80, 80, Rgba([0, 0, 255, 255]));
+/// let line = Components::Line(10.0, 10.0, 80.0, 80.0, Rgba([0, 0, 255, 255]));
 ///
 /// // Create a new text component
-/// let text = Components::Text(30, 40, 16, "Hello, Rust!", Rgba([255, 255, 255, 255]), Some((Rgba([0, 0, 0, 255]), 2)));
+/// let text = Components::Text(30.0, 40.0, 16, "Hello, Rust!", Rgba([255, 255, 255, 255]), Some((Rgba([0, 0, 0, 255]), 2)));
 /// ```
 ///
 /// # Methods
@@ -121,6 +159,9 @@ pub enum Component {
 /// - `Rectangle`: Creates a new rectangle component with specified attributes.
 /// - `Line`: Creates a new line component with specified attributes.
 /// - `Text`: Creates a new text component with specified attributes, including an optional border.
+/// - `StyledText`: Creates a new text component with a `TextStyle`, e.g. for wrapped multi-line text.
+/// - `RichText`: Creates a new text component made of several spans with their own fonts, sizes and colors.
+/// - `Group`: Creates a new component drawing several components together as one layer.
 ///
 /// # Note
 ///
@@ -142,9 +183,9 @@ impl Components {
     ///
     /// # Returns
     ///
-    /// A `Component::Circle` instance.
-    pub fn Circle(cx: u32, cy: u32, r: u32, color: Rgba<u8>) -> Component {
-        Component::Circle { cx, cy, r, color }
+    /// A circle `Component`.
+    pub fn Circle(cx: f32, cy: f32, r: f32, color: Rgba<u8>) -> Component {
+        Component::new(ComponentKind::Circle { cx, cy, r, color })
     }
 
     /// Creates a new rectangle component.
@@ -159,9 +200,9 @@ impl Components {
     ///
     /// # Returns
     ///
-    /// A `Component::Rectangle` instance.
-    pub fn Rectangle(h: u32, w: u32, x: u32, y: u32, color: Rgba<u8>) -> Component {
-        Component::Rectangle { h, w, x, y, color }
+    /// A rectangle `Component`.
+    pub fn Rectangle(h: f32, w: f32, x: f32, y: f32, color: Rgba<u8>) -> Component {
+        Component::new(ComponentKind::Rectangle { h, w, x, y, color })
     }
 
     /// Creates a new line component.
@@ -176,15 +217,15 @@ impl Components {
     ///
     /// # Returns
     ///
-    /// A `Component::Line` instance.
-    pub fn Line(x1: u32, y1: u32, x2: u32, y2: u32, color: Rgba<u8>) -> Component {
-        Component::Line {
+    /// A line `Component`.
+    pub fn Line(x1: f32, y1: f32, x2: f32, y2: f32, color: Rgba<u8>) -> Component {
+        Component::new(ComponentKind::Line {
             x1,
             y1,
             x2,
             y2,
             color,
-        }
+        })
     }
 
     /// Creates a new text component.
@@ -200,62 +241,488 @@ impl Components {
     ///
     /// # Returns
     ///
-    /// A `Component::Text` instance.
+    /// A text `Component`.
     pub fn Text(
-        x: u32,
-        y: u32,
+        x: f32,
+        y: f32,
         size: u32,
         text: &'static str,
         color: Rgba<u8>,
         border: Option<(Rgba<u8>, u32)>,
     ) -> Component {
-        Component::Text {
+        Component::new(ComponentKind::Text {
             x,
             y,
-            size,
-            text,
-            color,
-            border,
+            spans: vec![Span::new(text, size, color)],
+            style: TextStyle {
+                outline: border.map(|(color, width)| Outline {
+                    color,
+                    width: width as f32,
+                    join: LineJoin::Round,
+                }),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Creates a new text component with the specified style.
+    ///
+    /// Newlines in `text` always start a new line, and the style can be used to wrap
+    /// long lines and to control the spacing between lines.
+    ///
+    /// # Parameters
+    ///
+    /// - `x`: X-coordinate of the text.
+    /// - `y`: Y-coordinate of the text.
+    /// - `size`: Font size of the text.
+    /// - `text`: The actual text content.
+    /// - `color`: RGBA color of the text.
+    /// - `style`: Layout and styling options of the text.
+    ///
+    /// # Returns
+    ///
+    /// A text `Component`.
+    pub fn StyledText(
+        x: f32,
+        y: f32,
+        size: u32,
+        text: &'static str,
+        color: Rgba<u8>,
+        style: TextStyle,
+    ) -> Component {
+        Component::new(ComponentKind::Text {
+            x,
+            y,
+            spans: vec![Span::new(text, size, color)],
+            style,
+        })
+    }
+
+    /// Creates a new rich text component.
+    ///
+    /// The spans are laid out one after another on shared baselines, and wrapped and
+    /// aligned together as described by the style.
+    ///
+    /// # Parameters
+    ///
+    /// - `x`: X-coordinate of the text.
+    /// - `y`: Y-coordinate of the text.
+    /// - `spans`: Spans making up the content of the text.
+    /// - `style`: Layout and styling options of the text.
+    ///
+    /// # Returns
+    ///
+    /// A text `Component`.
+    pub fn RichText(x: f32, y: f32, spans: Vec<Span>, style: TextStyle) -> Component {
+        Component::new(ComponentKind::Text { x, y, spans, style })
+    }
+
+    /// Creates a new group component.
+    ///
+    /// The components of the group are drawn in order onto a transparent layer of their own,
+    /// which is then combined with the canvas as a whole. The opacity, blend mode and
+    /// operator of the group therefore apply to the group at once, so that overlapping
+    /// components of a translucent group do not show through each other.
+    ///
+    /// # Parameters
+    ///
+    /// - `components`: Components of the group.
+    ///
+    /// # Returns
+    ///
+    /// A group `Component`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use omage::{Components, Rgba};
+    ///
+    /// // A translucent watermark made of two overlapping shapes.
+    /// let watermark = Components::Group(vec![
+    ///     Components::Circle(50.0, 50.0, 30.0, Rgba([255, 255, 255, 255])),
+    ///     Components::Rectangle(20.0, 80.0, 10.0, 40.0, Rgba([255, 255, 255, 255])),
+    /// ])
+    /// .opacity(0.3);
+    /// ```
+    pub fn Group(components: Vec<Component>) -> Component {
+        Component::new(ComponentKind::Group { components })
+    }
+}
+
+impl Component {
+    /// Creates a component drawn over the canvas with the normal blend mode.
+    fn new(kind: ComponentKind) -> Self {
+        Self {
+            kind,
+            operator: CompositeOp::SourceOver,
+            blend_mode: BlendMode::Normal,
+            opacity: 1.0,
+            transform: Transform::identity(),
+            clips: Vec::new(),
+            masks: Vec::new(),
+            z_index: 0,
+        }
+    }
+
+    /// Sets the Porter-Duff operator combining the component with the canvas.
+    pub fn operator(mut self, operator: CompositeOp) -> Self {
+        self.operator = operator;
+        self
+    }
+
+    /// Sets the blend mode mixing the colors of the component with the canvas.
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// Sets the opacity of the component, from `0.0` (invisible) to `1.0` (opaque).
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Applies a transform to the component, after any transform applied before.
+    ///
+    /// The components of a group are transformed by their own transforms first and then by
+    /// the transform of the group.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transform = self.transform.then(&transform);
+        self
+    }
+
+    /// Restricts the drawing of the component to a region.
+    ///
+    /// Clipping a component several times restricts it to the intersection of the regions.
+    pub fn clip(mut self, clip: Clip) -> Self {
+        self.clips.push(clip);
+        self
+    }
+
+    /// Sets the opacity of the component with a soft mask, e.g. to fade it out.
+    ///
+    /// Masking a component several times multiplies the opacities of the masks.
+    pub fn mask(mut self, mask: SoftMask, mode: MaskMode) -> Self {
+        self.masks.push((mask, mode));
+        self
+    }
+
+    /// Sets the z-index of the component, `0` by default.
+    ///
+    /// Components with lower z-indices are drawn first, below components with higher ones,
+    /// and components with the same z-index are drawn in the order they were added. The
+    /// components of a group are ordered among themselves.
+    pub fn z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// Returns the components in the order they are drawn in, sorted by z-index.
+    pub(crate) fn drawing_order<'a>(
+        components: impl IntoIterator<Item = &'a Component>,
+    ) -> Vec<&'a Component> {
+        let mut ordered: Vec<&Component> = components.into_iter().collect();
+        // The sort is stable, so components with the same z-index keep their order.
+        ordered.sort_by_key(|component| component.z_index);
+        ordered
+    }
+
+    /// Draws the component inside a parent transformed by `parent`, e.g. a group.
+    pub(crate) fn draw_transformed(
+        &self,
+        config: Config,
+        canvas: &mut Canvas,
+        parent: &Transform,
+    ) -> Result<(), Box<dyn Error>> {
+        let transform = self.transform.then(parent);
+        let group = matches!(self.kind, ComponentKind::Group { .. });
+        if self.operator == CompositeOp::SourceOver
+            && self.blend_mode == BlendMode::Normal
+            && self.opacity >= 1.0
+            && self.clips.is_empty()
+            && self.masks.is_empty()
+            && !group
+        {
+            // Drawing straight onto the canvas is the same as compositing a layer over it.
+            return self.kind.draw(config, canvas, &transform);
         }
+
+        let mut layer = Canvas::new(canvas.width(), canvas.height());
+        self.kind.draw(config, &mut layer, &transform)?;
+        for clip in &self.clips {
+            let mask = clip.mask(&transform, layer.width(), layer.height());
+            for (x, y, pixel) in layer.enumerate_pixels_mut() {
+                let coverage = mask
+                    .as_ref()
+                    .map_or(0.0, |mask| mask.get(x as i32, y as i32));
+                pixel.0 = pixel.0.map(|c| c * coverage);
+            }
+        }
+        for (mask, mode) in &self.masks {
+            let opacity = mask.opacity(*mode, config, layer.width(), layer.height(), &transform)?;
+            for (pixel, opacity) in layer.pixels_mut().zip(opacity) {
+                pixel.0 = pixel.0.map(|c| c * opacity);
+            }
+        }
+        if self.opacity < 1.0 {
+            for pixel in layer.pixels_mut() {
+                pixel.0 = pixel.0.map(|c| c * self.opacity);
+            }
+        }
+        composite(canvas, &layer, self.operator, self.blend_mode);
+        Ok(())
     }
 }
 
 impl ComponentTrait for Component {
+    fn draw(&self, config: Config, canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+        self.draw_transformed(config, canvas, &Transform::identity())
+    }
+}
+
+impl ComponentKind {
+    /// Draws the component through `transform` onto the canvas.
     fn draw(
         &self,
         config: Config,
-        buffer: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+        canvas: &mut Canvas,
+        transform: &Transform,
     ) -> Result<(), Box<dyn Error>> {
         match *self {
-            Component::Circle { cx, cy, r, color } => {
-                let circle = Circle::new(cx, cy, r, color);
-                circle.draw(config, buffer)
+            ComponentKind::Circle { cx, cy, r, color } => {
+                let circle = Circle::new(cx, cy, r, color, *transform);
+                circle.draw(config, canvas)
             }
-            Component::Rectangle { h, w, x, y, color } => {
-                let rectangle = Rectangle::new(h, w, x, y, color);
-                rectangle.draw(config, buffer)
+            ComponentKind::Rectangle { h, w, x, y, color } => {
+                let rectangle = Rectangle::new(h, w, x, y, color, *transform);
+                rectangle.draw(config, canvas)
             }
-            Component::Line {
+            ComponentKind::Line {
                 x1,
                 y1,
                 x2,
                 y2,
                 color,
             } => {
-                let line = Line::new(x1, y1, x2, y2, color);
-                line.draw(config, buffer)
+                let line = Line::new(x1, y1, x2, y2, color, *transform);
+                line.draw(config, canvas)
             }
-            Component::Text {
+            ComponentKind::Text {
                 x,
                 y,
-                size,
-                text,
-                color,
-                border,
+                ref spans,
+                ref style,
             } => {
-                let text = Text::new(x, y, size, text, color, border);
-                text.draw(config, buffer)
+                let text = Text::new(x, y, spans.clone(), style.clone(), *transform);
+                text.draw(config, canvas)
+            }
+            ComponentKind::Group { ref components } => {
+                for component in Component::drawing_order(components) {
+                    component.draw_transformed(config, canvas, transform)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Handles a component reaching beyond the canvas as set by the canvas policy.
+///
+/// # Errors
+///
+/// Returns an error if the canvas policy is `CanvasPolicy::Error`.
+fn off_canvas(config: Config) -> Result<(), Box<dyn Error>> {
+    match config.canvas_policy {
+        CanvasPolicy::Clip => Ok(()),
+        CanvasPolicy::Error => Err(Box::new(CustomError::OutOfCanvas)),
+        CanvasPolicy::Warn(warn) => {
+            warn("Component clipped at the edge of the canvas");
+            Ok(())
+        }
+    }
+}
+
+/// Fills a path with `color`, with anti-aliased edges.
+///
+/// Shapes are always filled from their outlines, whether they are transformed or not, so
+/// that they look the same when they are moved by a fraction of a pixel or transformed.
+///
+/// # Errors
+///
+/// Returns an error if the filled path goes beyond the canvas boundaries and the canvas
+/// policy is `CanvasPolicy::Error`.
+fn fill_path(
+    path: &Path,
+    color: Rgba<u8>,
+    config: Config,
+    canvas: &mut Canvas,
+) -> Result<(), Box<dyn Error>> {
+    if path.bounds().is_none() {
+        return Ok(());
+    }
+    // The margin of one pixel around the canvas catches the coverage of paths beyond it.
+    match path.rasterize(config.width, config.height, 1) {
+        Some(mask) => {
+            if !mask.within(config.width, config.height) {
+                off_canvas(config)?;
             }
+            mask.draw(canvas, color, config.color_space);
         }
+        None => off_canvas(config)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::{Highlight, Shadow};
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Number of warnings reported on the current thread.
+        static WARNINGS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Counts a warning of `CanvasPolicy::Warn`.
+    fn count_warning(_: &str) {
+        WARNINGS.with(|warnings| warnings.set(warnings.get() + 1));
+    }
+
+    /// Draws a component on a transparent 100×100 canvas with the given canvas policy, with
+    /// the font of the examples for text.
+    fn draw(component: &Component, policy: CanvasPolicy) -> Result<Canvas, Box<dyn Error>> {
+        let font = Some("fonts/Roboto-Medium.ttf");
+        let config = Config::new(100, 100, Rgba([0, 0, 0, 0]), None, "output.png", font)
+            .canvas_policy(policy);
+        let mut canvas = Canvas::new(100, 100);
+        component.draw(config, &mut canvas)?;
+        Ok(canvas)
+    }
+
+    /// Returns whether the component can be drawn with `CanvasPolicy::Error`, after checking
+    /// that `CanvasPolicy::Warn` warns exactly when it cannot.
+    fn fits(component: &Component) -> bool {
+        WARNINGS.with(|warnings| warnings.set(0));
+        draw(component, CanvasPolicy::Warn(count_warning)).unwrap();
+        let fits = draw(component, CanvasPolicy::Error).is_ok();
+        assert_eq!(WARNINGS.with(Cell::get), usize::from(!fits));
+        fits
+    }
+
+    #[test]
+    fn shapes_inside_the_canvas_fit() {
+        let white = Rgba([255, 255, 255, 255]);
+        assert!(fits(&Components::Circle(50.0, 50.0, 49.0, white)));
+        assert!(fits(&Components::Line(0.0, 0.0, 99.0, 99.0, white)));
+        assert!(fits(&Components::Rectangle(50.0, 50.0, 25.0, 25.0, white)));
+    }
+
+    #[test]
+    fn shapes_touching_the_edges_fit() {
+        let white = Rgba([255, 255, 255, 255]);
+        assert!(fits(&Components::Rectangle(100.0, 100.0, 0.0, 0.0, white)));
+        assert!(fits(&Components::Circle(49.5, 49.5, 50.0, white)));
+        assert!(fits(&Components::Line(0.0, 0.0, 99.0, 0.0, white)));
+    }
+
+    #[test]
+    fn shapes_beyond_the_canvas_do_not_fit() {
+        let white = Rgba([255, 255, 255, 255]);
+        assert!(!fits(&Components::Rectangle(
+            20.0, 20.0, -10.0, 40.0, white
+        )));
+        assert!(!fits(&Components::Circle(95.0, 50.0, 10.0, white)));
+        assert!(!fits(&Components::Line(-5.0, 50.0, 50.0, 50.0, white)));
+        assert!(!fits(&Components::Rectangle(
+            10.0, 10.0, 200.0, 200.0, white
+        )));
+    }
+
+    #[test]
+    fn components_are_ordered_by_z_index_then_insertion() {
+        let white = Rgba([255, 255, 255, 255]);
+        let circle = |z_index| Components::Circle(50.0, 50.0, 10.0, white).z_index(z_index);
+        let components = [circle(1), circle(0), circle(1), circle(-1), circle(0)];
+        let order: Vec<usize> = Component::drawing_order(&components)
+            .into_iter()
+            .map(|drawn| {
+                let index = components.iter().position(|c| std::ptr::eq(c, drawn));
+                index.unwrap()
+            })
+            .collect();
+        assert_eq!(order, [3, 1, 4, 0, 2]);
+    }
+
+    #[test]
+    fn text_fits_only_with_its_effects() {
+        let white = Rgba([255, 255, 255, 255]);
+        let text = |style| Components::StyledText(10.0, 30.0, 20, "Hi", white, style);
+        assert!(fits(&text(TextStyle::default())));
+
+        let shadow = Shadow {
+            offset: (90.0, 0.0),
+            color: white,
+            blur: 0,
+        };
+        let outline = Outline {
+            color: white,
+            width: 40.0,
+            join: LineJoin::Round,
+        };
+        let highlight = Highlight {
+            color: white,
+            padding: 40.0,
+            corner_radius: 0.0,
+        };
+        assert!(!fits(&text(TextStyle {
+            shadow: Some(shadow),
+            ..TextStyle::default()
+        })));
+        assert!(!fits(&text(TextStyle {
+            outline: Some(outline),
+            ..TextStyle::default()
+        })));
+        assert!(!fits(&text(TextStyle {
+            highlight: Some(highlight),
+            ..TextStyle::default()
+        })));
+    }
+
+    #[test]
+    fn transformed_shapes_are_filled_like_untransformed_ones() {
+        let white = Rgba([255, 255, 255, 255]);
+        let rectangle = Components::Rectangle(10.0, 20.0, 40.0, 45.0, white)
+            .transform(Transform::rotate_around(90.0, 50.0, 50.0));
+        let upright = Components::Rectangle(20.0, 10.0, 45.0, 40.0, white);
+        let (rotated, upright) = (
+            draw(&rectangle, CanvasPolicy::Clip).unwrap(),
+            draw(&upright, CanvasPolicy::Clip).unwrap(),
+        );
+        for (a, b) in rotated.pixels().zip(upright.pixels()) {
+            assert!((a[3] - b[3]).abs() < 1e-3);
+        }
+
+        // Moving a shape by half a pixel covers the pixels on its edges by half.
+        let moved = Components::Rectangle(10.0, 10.0, 20.0, 20.0, white)
+            .transform(Transform::translate(0.5, 0.0));
+        let canvas = draw(&moved, CanvasPolicy::Clip).unwrap();
+        assert!((canvas.get_pixel(20, 25)[3] - 0.5).abs() < 1e-3);
+        assert_eq!(canvas.get_pixel(25, 25)[3], 1.0);
+        assert!((canvas.get_pixel(30, 25)[3] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn shapes_are_clipped_at_the_edges() {
+        let white = Rgba([255, 255, 255, 255]);
+        let rectangle = Components::Rectangle(20.0, 20.0, -10.0, 40.0, white);
+        let canvas = draw(&rectangle, CanvasPolicy::Clip).unwrap();
+        assert_eq!(canvas.get_pixel(0, 50)[3], 1.0);
+        assert_eq!(canvas.get_pixel(10, 50)[3], 0.0);
+
+        // Huge shapes are drawn without allocating their whole area.
+        let huge = Components::Rectangle(70000.0, 70000.0, -35000.0, -35000.0, white);
+        let canvas = draw(&huge, CanvasPolicy::Clip).unwrap();
+        assert!(canvas.pixels().all(|pixel| pixel[3] == 1.0));
     }
 }
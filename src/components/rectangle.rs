@@ -1,57 +1,54 @@
-use image::Pixel;
-
-use super::{ComponentTrait, Config, CustomError, Error, ImageBuffer, Rgba};
+use super::{fill_path, Canvas, ComponentTrait, Config, Error, Rgba};
+use crate::path::Path;
+use crate::transform::Transform;
 
 /// Represents a rectangle component with a specified height (`h`), width (`w`), position (`(x, y)`), and color.
 #[derive(Clone, Copy)]
 pub struct Rectangle {
     /// Height of the rectangle.
-    h: u32,
+    h: f32,
     /// Width of the rectangle.
-    w: u32,
+    w: f32,
     /// X-coordinate of the top-left corner of the rectangle.
-    x: u32,
+    x: f32,
     /// Y-coordinate of the top-left corner of the rectangle.
-    y: u32,
+    y: f32,
     /// Color of the rectangle in Rgba format.
     color: Rgba<u8>,
+    /// Transform from the coordinates of the rectangle to the canvas.
+    transform: Transform,
 }
 
 impl Rectangle {
     /// Creates a new rectangle with the specified parameters.
-    pub fn new(h: u32, w: u32, x: u32, y: u32, color: Rgba<u8>) -> Self {
-        Self { h, w, x, y, color }
+    pub fn new(h: f32, w: f32, x: f32, y: f32, color: Rgba<u8>, transform: Transform) -> Self {
+        Self {
+            h,
+            w,
+            x,
+            y,
+            color,
+            transform,
+        }
     }
 }
 
 impl ComponentTrait for Rectangle {
-    /// Draws the rectangle on the provided image buffer using the specified configuration.
+    /// Draws the rectangle on the provided canvas using the specified configuration.
     ///
     /// # Arguments
     ///
     /// * `config` - Configuration for the drawing canvas.
-    /// * `buffer` - Image buffer to draw the rectangle on.
+    /// * `canvas` - Canvas to draw the rectangle on.
     ///
     /// # Errors
     ///
-    /// Returns an error if the rectangle goes beyond the canvas boundaries.
-    fn draw(
-        &self,
-        config: Config,
-        buffer: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
-    ) -> Result<(), Box<dyn Error>> {
-        for x in self.x..self.x + self.w {
-            if x > config.width {
-                return Err(Box::new(CustomError::OutOfCanvas));
-            }
-            for y in self.y..self.y + self.h {
-                if y > config.height {
-                    return Err(Box::new(CustomError::OutOfCanvas));
-                }
-                buffer.get_pixel_mut(x, y).blend(&self.color)
-            }
-        }
-
-        Ok(())
+    /// Returns an error if the rectangle goes beyond the canvas boundaries and the canvas
+    /// policy is `CanvasPolicy::Error`.
+    fn draw(&self, config: Config, canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+        let mut path = Path::new();
+        path.set_transform(self.transform);
+        path.add_rect(self.x, self.y, self.w, self.h);
+        fill_path(&path, self.color, config, canvas)
     }
 }
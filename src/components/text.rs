@@ -1,153 +1,470 @@
 use crate::error::CustomError;
+use crate::mask::Mask;
+use crate::path::Path;
+use crate::sprite::Sprite;
+use crate::text::layout::{layout, Decorations, Run};
+use crate::text::{
+    fit::fit_text, text_path::Polyline, Font, HorizontalAlign, LineJoin, Shadow, Span, TextStyle,
+};
+use crate::transform::Transform;
 
-use super::ComponentTrait;
-use image::{ImageBuffer, Pixel, Rgba};
-use rusttype::{point, Font, Scale};
-use std::io::Read;
+use super::{off_canvas, Canvas, ComponentTrait};
+use image::RgbaImage;
+use std::sync::Arc;
 
-/// Represents a text component with a specified position, size, content, and color.
+/// Represents a text component with a specified position, content and style.
 pub struct Text {
     /// X-coordinate of the top-left corner of the text.
-    x: u32,
+    x: f32,
     /// Y-coordinate of the top-left corner of the text.
-    y: u32,
-    /// Size of the text.
-    size: u32,
-    /// Text content of the text.
-    text: &'static str,
-    /// Color of the text in Rgba format.
-    color: Rgba<u8>,
-    /// Border (Color (Rgba<u8>), Border size).
-    border: Option<(Rgba<u8>, u32)>,
+    y: f32,
+    /// Spans making up the content of the text.
+    spans: Vec<Span>,
+    /// Layout and styling options of the text.
+    style: TextStyle,
+    /// Transform from the coordinates of the text to the canvas.
+    transform: Transform,
 }
 
 impl Text {
     /// Creates a new text component with the specified parameters.
-    pub fn new(
-        x: u32,
-        y: u32,
-        size: u32,
-        text: &'static str,
-        color: Rgba<u8>,
-        border: Option<(Rgba<u8>, u32)>,
-    ) -> Self {
+    pub fn new(x: f32, y: f32, spans: Vec<Span>, style: TextStyle, transform: Transform) -> Self {
         Self {
             x,
             y,
-            size,
-            text,
-            color,
-            border,
+            spans,
+            style,
+            transform,
         }
     }
 }
 
 impl ComponentTrait for Text {
-    /// Draws the text on the provided image buffer using the specified configuration.
+    /// Draws the text on the provided canvas using the specified configuration.
     ///
     /// # Arguments
     ///
     /// * `config` - Configuration for the drawing canvas.
-    /// * `buffer` - Image buffer to draw the text on.
+    /// * `canvas` - Canvas to draw the text on.
     ///
     /// # Errors
     ///
-    /// Returns an error if there is an issue with the font or drawing the text.
+    /// Returns an error if there is an issue with the font or drawing the text, or if the
+    /// text goes beyond the canvas boundaries and the canvas policy is `CanvasPolicy::Error`.
     fn draw(
         &self,
         config: crate::Config,
-        buffer: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+        canvas: &mut Canvas,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(font_path) = config.font_path {
-            let mut fonts = std::fs::File::open(font_path)?;
-            let mut bytes = Vec::new();
-            fonts.read_to_end(&mut bytes)?;
-            let font = Font::try_from_vec(bytes).expect("Error Constructing Font");
-            let scale = Scale::uniform(self.size as f32);
-
-            if self.border.is_some() {
-                for i in 0..self.border.unwrap().1 {
-                    render_text(
-                        buffer,
-                        &font,
-                        scale,
-                        self.border.unwrap().0,
-                        self.text,
-                        (self.x + i, self.y),
-                    );
-                    render_text(
-                        buffer,
-                        &font,
-                        scale,
-                        self.border.unwrap().0,
-                        self.text,
-                        (self.x - i, self.y),
-                    );
-                    render_text(
-                        buffer,
-                        &font,
-                        scale,
-                        self.border.unwrap().0,
-                        self.text,
-                        (self.x, self.y + i),
-                    );
-                    render_text(
-                        buffer,
-                        &font,
-                        scale,
-                        self.border.unwrap().0,
-                        self.text,
-                        (self.x, self.y - i),
-                    );
+        // Load every font once, even if several spans use it.
+        let mut loaded: Vec<(&str, Font)> = Vec::new();
+        let mut fonts = Vec::new();
+        for span in &self.spans {
+            let path = match span.font_path.or(config.font_path) {
+                Some(path) => path,
+                None => return Err(Box::new(CustomError::NoFontProvided)),
+            };
+            let font = match loaded.iter().find(|(p, _)| *p == path) {
+                Some((_, font)) => font.clone(),
+                None => {
+                    let font = Font::from_file(path)?;
+                    loaded.push((path, font.clone()));
+                    font
                 }
+            };
+            let font = span.variations.iter().fold(font, |font, &(axis, value)| {
+                font.with_variation(axis, value)
+            });
+            fonts.push(font);
+        }
+
+        let runs: Vec<Run> = self
+            .spans
+            .iter()
+            .zip(&fonts)
+            .map(|(span, font)| Run {
+                font,
+                size: span.size,
+                text: span.text.to_string(),
+                bold: span.bold || self.style.bold,
+                italic: span.italic || self.style.italic,
+            })
+            .collect();
+        let (runs, style) = fit_text(&runs, &self.style);
+
+        if let Some(rendered) = render_text(
+            &runs,
+            &self.spans,
+            (self.x, self.y),
+            &style,
+            &self.transform,
+            (config.width, config.height),
+        ) {
+            let outline = style.outline.map(|outline| {
+                let width = outline.width * self.transform.scale_factor();
+                (rendered.text.dilate(width, outline.join), outline.color)
+            });
+            let shadow = style.shadow.map(|shadow| {
+                // The shadow is cast by the text together with its outline.
+                let silhouette = outline
+                    .as_ref()
+                    .map_or(&rendered.text, |(outline, _)| outline);
+                let (dx, dy, blur) = shadow_on_canvas(&shadow, &self.transform);
+                (silhouette.translate(dx, dy).blur(blur), shadow.color)
+            });
+            let highlight = style
+                .highlight
+                .zip(rendered.highlight)
+                .map(|(highlight, mask)| (mask, highlight.color));
+
+            // The text is checked against the canvas together with everything drawn
+            // around it.
+            let effects = [&highlight, &outline, &shadow];
+            let masks = effects.into_iter().flatten().map(|(mask, _)| mask);
+            if !std::iter::once(&rendered.text)
+                .chain(masks)
+                .all(|mask| mask.within(config.width, config.height))
+            {
+                off_canvas(config)?;
+            }
+
+            if let Some((mask, color)) = highlight {
+                mask.draw(canvas, color, config.color_space);
+            }
+            if let Some((shadow, color)) = shadow {
+                shadow.draw(canvas, color, config.color_space);
+            }
+            if let Some((outline, color)) = outline {
+                outline.draw(canvas, color, config.color_space);
+            }
+            for (run_mask, span) in rendered.runs.iter().zip(&self.spans) {
+                if let Some(run_mask) = run_mask {
+                    run_mask.draw(canvas, span.color, config.color_space);
+                }
+            }
+            for (image, transform) in &rendered.color_glyphs {
+                Sprite::new(image, *transform).draw(canvas, config.color_space);
             }
-            render_text(
-                buffer,
-                &font,
-                scale,
-                self.color,
-                self.text,
-                (self.x, self.y),
-            );
-        } else {
-            return Err(Box::new(CustomError::NoFontProvided));
         }
 
         Ok(())
     }
 }
 
+/// Coverage masks of rendered text.
+struct Rendered {
+    /// Mask of the whole text, including color glyphs.
+    text: Mask,
+    /// Mask of every run, `None` for runs without any coverage.
+    runs: Vec<Option<Mask>>,
+    /// Mask of the highlight box behind the text.
+    highlight: Option<Mask>,
+    /// Images of color glyphs, with the transforms placing them on the canvas.
+    color_glyphs: Vec<(Arc<RgbaImage>, Transform)>,
+}
+
+/// Lays out the runs and renders the coverage of their glyphs and decorations into masks.
+///
+/// The glyphs are rotated around `position`, or placed along the text path, from their
+/// outlines before they are rasterized, so rotated text is anti-aliased just like
+/// horizontal text. The placed glyphs are then mapped to the canvas through `transform`.
+/// Glyphs with a color image, such as emoji, are returned as images to draw on top of the
+/// filled runs instead.
+///
+/// Only the parts of the text that can reach a canvas of the size `canvas`, together with
+/// their outline and shadow, are rasterized.
+///
+/// Returns `None` if nothing would be drawn, e.g. for empty text.
 fn render_text(
-    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
-    font: &Font<'static>,
-    scale: Scale,
-    color: Rgba<u8>,
-    text: &str,
-    position: (u32, u32),
-) {
-    let v_metrics = font.v_metrics(scale);
-
-    let glyphs: Vec<_> = font
-        .layout(
-            text,
-            scale,
-            point(position.0 as f32, position.1 as f32 + v_metrics.ascent),
-        )
-        .collect();
+    runs: &[Run],
+    spans: &[Span],
+    position: (f32, f32),
+    style: &TextStyle,
+    transform: &Transform,
+    canvas: (u32, u32),
+) -> Option<Rendered> {
+    let margin = effects_reach(style, transform) + 1;
+    let mut paths = vec![Path::new(); runs.len()];
+    let mut highlight = None;
+    let mut color_glyphs = Vec::new();
 
-    for glyph in glyphs {
-        if let Some(bounding_box) = glyph.pixel_bounding_box() {
-            // Draw the glyph into the image per-pixel by using the draw closure
-            glyph.draw(|x, y, v| {
-                let color = Rgba([color[0], color[1], color[2], (v * color[3] as f32) as u8]);
-
-                if x + (bounding_box.min.x as u32) < img.width()
-                    && y + (bounding_box.min.y as u32) < img.height()
-                {
-                    img.get_pixel_mut(x + bounding_box.min.x as u32, y + bounding_box.min.y as u32)
-                        .blend(&color);
-                }
+    // Transforms from the coordinates of a glyph, with its origin on the baseline, to the
+    // canvas, line by line.
+    let placed: Vec<Vec<_>> = if let Some(text_path) = &style.path {
+        // Lay the text out around (0, 0), then bend each glyph onto the path.
+        let polyline = Polyline::new(text_path, position);
+        let start = match style.align {
+            HorizontalAlign::Left => 0.0,
+            HorizontalAlign::Center => polyline.length() / 2.0,
+            HorizontalAlign::Right => polyline.length(),
+        };
+        let style = TextStyle {
+            bounds: None,
+            ..style.clone()
+        };
+        let layout = layout(runs, (0.0, 0.0), &style);
+
+        layout
+            .lines
+            .into_iter()
+            .map(|line| {
+                line.glyphs
+                    .into_iter()
+                    .map(|glyph| {
+                        let origin = glyph.position;
+                        let half_advance = glyph.advance / 2.0;
+                        let (point, angle) = polyline.at(start + origin.x + half_advance);
+                        let placement = Transform::translate(-half_advance, origin.y)
+                            .then(&Transform::rotate(angle))
+                            .then(&Transform::translate(point.0, point.1))
+                            .then(transform);
+                        (glyph, placement)
+                    })
+                    .collect()
+            })
+            .collect()
+    } else {
+        let layout = layout(runs, position, style);
+        let rotation =
+            Transform::rotate_around(style.rotation, position.0, position.1).then(transform);
+
+        if let Some(options) = style.highlight {
+            let left = layout
+                .lines
+                .iter()
+                .map(|line| line.x)
+                .fold(f32::MAX, f32::min);
+            let right = layout
+                .lines
+                .iter()
+                .map(|line| line.x + line.width)
+                .fold(f32::MIN, f32::max);
+            let padding = options.padding;
+            let mut path = Path::new();
+            path.set_transform(rotation);
+            path.add_rounded_rect(
+                left - padding,
+                layout.y - padding,
+                right - left + 2.0 * padding,
+                layout.height + 2.0 * padding,
+                options.corner_radius,
+            );
+            highlight = path.rasterize(canvas.0, canvas.1, margin);
+        }
+
+        layout
+            .lines
+            .into_iter()
+            .map(|line| {
+                line.glyphs
+                    .into_iter()
+                    .map(|glyph| {
+                        let origin = glyph.position;
+                        let placement = Transform::translate(origin.x, origin.y).then(&rotation);
+                        (glyph, placement)
+                    })
+                    .collect()
             })
+            .collect()
+    };
+
+    for (glyph, placement) in placed.iter().flatten() {
+        let path = &mut paths[glyph.run];
+        let run = &runs[glyph.run];
+        let glyph_transform = run.glyph_transform().then(placement);
+        match run.font.color_glyph(glyph.id, run.size as f32) {
+            Some(color) => color_glyphs.push((
+                color.image,
+                Transform::translate(color.x, color.y).then(&glyph_transform),
+            )),
+            None => {
+                path.set_transform(glyph_transform);
+                run.font.build_outline(glyph.id, run.size as f32, path);
+            }
         }
     }
+
+    // Decorations of the glyphs of a run next to each other on a line are drawn as one, so
+    // that they also cover the letter spacing between the glyphs.
+    for segment in placed
+        .iter()
+        .flat_map(|line| line.chunk_by(|a, b| a.0.run == b.0.run))
+    {
+        let run = segment[0].0.run;
+        let (underline, strikethrough) = (
+            spans[run].underline || style.underline,
+            spans[run].strikethrough || style.strikethrough,
+        );
+        if !underline && !strikethrough {
+            continue;
+        }
+        let decorations = Decorations::new(&runs[run]);
+        // Glyphs bent onto a path each get their own part of the decorations, up to the next
+        // glyph, while straight lines get a single part from the first to the last glyph.
+        let parts: Vec<(Transform, f32)> = if style.path.is_some() {
+            segment
+                .iter()
+                .enumerate()
+                .map(|(i, (glyph, placement))| {
+                    let width = segment.get(i + 1).map_or(glyph.advance, |(next, _)| {
+                        (next.position.x - glyph.position.x).max(glyph.advance)
+                    });
+                    (*placement, width)
+                })
+                .collect()
+        } else {
+            let (first, placement) = &segment[0];
+            let left = segment
+                .iter()
+                .map(|(glyph, _)| glyph.position.x)
+                .fold(f32::MAX, f32::min);
+            let right = segment
+                .iter()
+                .map(|(glyph, _)| glyph.position.x + glyph.advance)
+                .fold(f32::MIN, f32::max);
+            let start = Transform::translate(left - first.position.x, 0.0).then(placement);
+            vec![(start, right - left)]
+        };
+
+        let path = &mut paths[run];
+        for (placement, width) in parts {
+            path.set_transform(placement);
+            if underline {
+                path.add_rect(
+                    0.0,
+                    decorations.underline,
+                    width,
+                    decorations.underline_thickness,
+                );
+            }
+            if strikethrough {
+                path.add_rect(
+                    0.0,
+                    decorations.strikethrough,
+                    width,
+                    decorations.strikethrough_thickness,
+                );
+            }
+        }
+    }
+
+    // Emboldened runs are grown from their rasterized shapes.
+    let run_masks: Vec<Option<Mask>> = paths
+        .iter()
+        .zip(runs)
+        .map(|(path, run)| {
+            let mask = path.rasterize(canvas.0, canvas.1, margin + run.embolden())?;
+            Some(match run.embolden() {
+                0 => mask,
+                radius if run.font.is_bitmap() => mask.dilate(radius as f32, LineJoin::Miter),
+                radius => mask.dilate(radius as f32, LineJoin::Round),
+            })
+        })
+        .collect();
+
+    let text = color_glyphs
+        .iter()
+        .filter_map(|(image, transform)| Sprite::new(image, *transform).mask())
+        .chain(run_masks.iter().flatten().cloned())
+        .reduce(|a, b| a.union(&b))?;
+
+    Some(Rendered {
+        text,
+        runs: run_masks,
+        highlight,
+        color_glyphs,
+    })
+}
+
+/// Returns the offset and the blur radius of a shadow on the canvas, which follow the
+/// transform of the text like its glyphs.
+fn shadow_on_canvas(shadow: &Shadow, transform: &Transform) -> (f32, f32, u32) {
+    let (dx, dy) = transform.apply_vector(shadow.offset.0, shadow.offset.1);
+    let blur = (shadow.blur as f32 * transform.scale_factor()).round() as u32;
+    (dx, dy, blur)
+}
+
+/// Returns how many pixels the outline and the shadow of the text, drawn through
+/// `transform`, reach beyond its glyphs.
+fn effects_reach(style: &TextStyle, transform: &Transform) -> u32 {
+    let outline = style.outline.map_or(0.0, |outline| {
+        (outline.width * transform.scale_factor()).max(0.0)
+    });
+    let shadow = style.shadow.map_or(0.0, |shadow| {
+        let (dx, dy, blur) = shadow_on_canvas(&shadow, transform);
+        dx.abs().max(dy.abs()) + blur as f32
+    });
+    (outline + shadow).ceil() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::layout::tests::monospace_font;
+    use crate::text::Outline;
+    use image::Rgba;
+
+    #[test]
+    fn underlines_cover_the_letter_spacing() {
+        let font = monospace_font();
+        let runs = [Run {
+            font: &font,
+            size: 10,
+            text: "abcd".to_string(),
+            bold: false,
+            italic: false,
+        }];
+        let spans = [Span::new("abcd", 10, Rgba([0, 0, 0, 255]))];
+        let style = TextStyle {
+            underline: true,
+            letter_spacing: 3.0,
+            ..TextStyle::default()
+        };
+        let rendered = render_text(
+            &runs,
+            &spans,
+            (0.0, 0.0),
+            &style,
+            &Transform::identity(),
+            (100, 20),
+        )
+        .unwrap();
+
+        let line = &layout(&runs, (0.0, 0.0), &style).lines[0];
+        let (first, last) = (&line.glyphs[0], &line.glyphs[3]);
+        let row = (first.position.y + Decorations::new(&runs[0]).underline) as i32;
+        let mask = rendered.runs[0].as_ref().unwrap();
+        let (left, right) = (first.position.x, last.position.x + last.advance);
+        assert!(right - left > 4.0 * 5.0, "the glyphs are spaced apart");
+        for x in left as i32..right as i32 {
+            assert_eq!(mask.get(x, row), 1.0, "({x}, {row})");
+        }
+    }
+
+    #[test]
+    fn shadows_follow_the_transform() {
+        let shadow = Shadow {
+            offset: (3.0, 0.0),
+            blur: 2,
+            color: Rgba([0, 0, 0, 255]),
+        };
+        let transform = Transform::rotate(90.0).then(&Transform::scale(2.0, 2.0));
+        let (dx, dy, blur) = shadow_on_canvas(&shadow, &transform);
+        assert!(dx.abs() < 1e-4 && (dy - 6.0).abs() < 1e-4, "({dx}, {dy})");
+        assert_eq!(blur, 4);
+    }
+
+    #[test]
+    fn outlines_reach_as_far_as_the_transform_scales_them() {
+        let style = TextStyle {
+            outline: Some(Outline {
+                color: Rgba([0, 0, 0, 255]),
+                width: 3.0,
+                join: LineJoin::Round,
+            }),
+            ..TextStyle::default()
+        };
+        assert_eq!(effects_reach(&style, &Transform::identity()), 3);
+        assert_eq!(effects_reach(&style, &Transform::scale(2.0, 2.0)), 6);
+    }
 }
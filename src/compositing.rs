@@ -0,0 +1,541 @@
+use image::{ImageBuffer, Rgba, RgbaImage};
+use std::sync::OnceLock;
+
+/// Color space in which colors are blended with the canvas.
+///
+/// Colors are stored in sRGB, where values are not proportional to the amount of light.
+/// Blending them directly is fast and matches most image editors, but makes anti-aliased
+/// edges and thin lines look too dark and mixes of translucent colors look muddy. Blending
+/// in linear light converts the colors to light intensities first, and keeps them as such
+/// until the image is saved.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{ColorSpace, Config, Rgba};
+///
+/// let config = Config::new(800, 600, Rgba([255, 255, 255, 255]), None, "output.png", None)
+///     .color_space(ColorSpace::LinearLight);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Colors are blended as they are stored.
+    #[default]
+    Srgb,
+    /// Colors are blended in linear light.
+    LinearLight,
+}
+
+impl ColorSpace {
+    /// Converts a channel of a color in this color space to a value between `0.0` and `1.0`
+    /// to blend with.
+    fn decode(self, value: u8) -> f32 {
+        match self {
+            ColorSpace::Srgb => value as f32 / 255.0,
+            ColorSpace::LinearLight => {
+                static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+                TABLE.get_or_init(|| {
+                    std::array::from_fn(|i| {
+                        let c = i as f32 / 255.0;
+                        if c <= 0.04045 {
+                            c / 12.92
+                        } else {
+                            ((c + 0.055) / 1.055).powf(2.4)
+                        }
+                    })
+                })[value as usize]
+            }
+        }
+    }
+
+    /// Converts a blended value between `0.0` and `1.0` back to a channel of a color.
+    fn encode(self, value: f32) -> u8 {
+        let value = value.clamp(0.0, 1.0);
+        let value = match self {
+            ColorSpace::Srgb => value,
+            ColorSpace::LinearLight => {
+                if value <= 0.0031308 {
+                    value * 12.92
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                }
+            }
+        };
+        (value * 255.0).round() as u8
+    }
+}
+
+/// A canvas being drawn on, with premultiplied colors between `0.0` and `1.0` in the color
+/// space that components are blended in.
+///
+/// Colors are only converted back to 8-bit sRGB once drawing is finished, so that blending
+/// many colors in linear light does not add up rounding errors, which would show as banding.
+pub(crate) type Canvas = ImageBuffer<Rgba<f32>, Vec<f32>>;
+
+/// Converts a color to a premultiplied pixel of a canvas blending in `space`.
+fn decode_pixel(color: Rgba<u8>, space: ColorSpace) -> Rgba<f32> {
+    let alpha = color[3] as f32 / 255.0;
+    Rgba([
+        space.decode(color[0]) * alpha,
+        space.decode(color[1]) * alpha,
+        space.decode(color[2]) * alpha,
+        alpha,
+    ])
+}
+
+/// Converts a premultiplied pixel of a canvas blending in `space` back to a color.
+pub(crate) fn encode_pixel(pixel: Rgba<f32>, space: ColorSpace) -> Rgba<u8> {
+    let alpha = pixel[3].clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    Rgba([
+        space.encode(pixel[0] / alpha),
+        space.encode(pixel[1] / alpha),
+        space.encode(pixel[2] / alpha),
+        (alpha * 255.0).round() as u8,
+    ])
+}
+
+/// Converts an image to a canvas blending in `space`.
+pub(crate) fn decode_image(image: &RgbaImage, space: ColorSpace) -> Canvas {
+    Canvas::from_fn(image.width(), image.height(), |x, y| {
+        decode_pixel(*image.get_pixel(x, y), space)
+    })
+}
+
+/// Converts a canvas blending in `space` back to an image.
+pub(crate) fn encode_image(canvas: &Canvas, space: ColorSpace) -> RgbaImage {
+    RgbaImage::from_fn(canvas.width(), canvas.height(), |x, y| {
+        encode_pixel(*canvas.get_pixel(x, y), space)
+    })
+}
+
+/// Draws `color` over a pixel of a canvas blending in `space`, with `coverage` between
+/// `0.0` and `1.0` scaling its alpha, e.g. on anti-aliased edges.
+pub(crate) fn blend(pixel: &mut Rgba<f32>, color: Rgba<u8>, coverage: f32, space: ColorSpace) {
+    let coverage = coverage.clamp(0.0, 1.0);
+    let source = decode_pixel(color, space);
+    let keep = 1.0 - source[3] * coverage;
+    for channel in 0..4 {
+        pixel[channel] = source[channel] * coverage + pixel[channel] * keep;
+    }
+}
+
+/// Porter-Duff operator used to combine a component (the source) with the canvas it is
+/// drawn on (the destination).
+///
+/// Components are drawn into a layer of their own, which is then combined with the canvas
+/// over the whole canvas. Operators such as `SourceIn` or `Copy` therefore also affect the
+/// canvas outside of the shape of the component.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{Components, CompositeOp, Rgba};
+///
+/// // Punch a circular hole into whatever has been drawn before.
+/// let hole = Components::Circle(50.0, 50.0, 20.0, Rgba([0, 0, 0, 255])).operator(CompositeOp::DestinationOut);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompositeOp {
+    /// The source is drawn over the destination.
+    #[default]
+    SourceOver,
+    /// The source is drawn only where the destination is, and the rest is cleared.
+    SourceIn,
+    /// The source is drawn only where the destination is not, and the rest is cleared.
+    SourceOut,
+    /// The source is drawn over the destination, but only where the destination is.
+    SourceAtop,
+    /// The source is drawn behind the destination.
+    DestinationOver,
+    /// The destination is kept only where the source is, and the rest is cleared.
+    DestinationIn,
+    /// The destination is erased where the source is.
+    DestinationOut,
+    /// The destination is drawn over the source, but only where the source is.
+    DestinationAtop,
+    /// The source and the destination are kept only where they do not overlap.
+    Xor,
+    /// The source replaces the destination, which is cleared outside of the source.
+    Copy,
+}
+
+impl CompositeOp {
+    /// Returns the fractions of the source and of the destination that are kept, given the
+    /// alpha of the source and of the destination.
+    fn factors(self, source_alpha: f32, destination_alpha: f32) -> (f32, f32) {
+        let (sa, da) = (source_alpha, destination_alpha);
+        match self {
+            CompositeOp::SourceOver => (1.0, 1.0 - sa),
+            CompositeOp::SourceIn => (da, 0.0),
+            CompositeOp::SourceOut => (1.0 - da, 0.0),
+            CompositeOp::SourceAtop => (da, 1.0 - sa),
+            CompositeOp::DestinationOver => (1.0 - da, 1.0),
+            CompositeOp::DestinationIn => (0.0, sa),
+            CompositeOp::DestinationOut => (0.0, 1.0 - sa),
+            CompositeOp::DestinationAtop => (1.0 - da, sa),
+            CompositeOp::Xor => (1.0 - da, 1.0 - sa),
+            CompositeOp::Copy => (1.0, 0.0),
+        }
+    }
+}
+
+/// Blend mode used to mix the colors of a component with the colors of the canvas below it,
+/// as in image editors.
+///
+/// The blend mode decides the color where the component overlaps the canvas, and the
+/// `CompositeOp` of the component then decides which parts of both are kept.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{BlendMode, Components, Rgba};
+///
+/// // Darken the canvas below the rectangle.
+/// let shade = Components::Rectangle(40.0, 60.0, 10.0, 20.0, Rgba([120, 120, 160, 255])).blend_mode(BlendMode::Multiply);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// The color of the component is used as is.
+    #[default]
+    Normal,
+    /// The colors are multiplied, which darkens the canvas.
+    Multiply,
+    /// The inverted colors are multiplied, which lightens the canvas.
+    Screen,
+    /// Multiplies dark parts and screens light parts of the canvas.
+    Overlay,
+    /// The darker of both colors is used.
+    Darken,
+    /// The lighter of both colors is used.
+    Lighten,
+    /// The canvas is brightened to reflect the color of the component.
+    ColorDodge,
+    /// The canvas is darkened to reflect the color of the component.
+    ColorBurn,
+    /// Multiplies or screens depending on the color of the component.
+    HardLight,
+    /// A softer version of `HardLight`.
+    SoftLight,
+    /// The darker color is subtracted from the lighter one.
+    Difference,
+    /// Like `Difference`, but with lower contrast.
+    Exclusion,
+    /// The hue of the component with the saturation and luminosity of the canvas.
+    Hue,
+    /// The saturation of the component with the hue and luminosity of the canvas.
+    Saturation,
+    /// The hue and saturation of the component with the luminosity of the canvas.
+    Color,
+    /// The luminosity of the component with the hue and saturation of the canvas.
+    Luminosity,
+}
+
+impl BlendMode {
+    /// Mixes a color of the canvas with a color of the component, with channels between
+    /// `0.0` and `1.0`.
+    fn mix(self, backdrop: [f32; 3], source: [f32; 3]) -> [f32; 3] {
+        let (b, s) = (backdrop, source);
+        let separable = |f: fn(f32, f32) -> f32| [f(b[0], s[0]), f(b[1], s[1]), f(b[2], s[2])];
+        match self {
+            BlendMode::Normal => s,
+            BlendMode::Multiply => separable(|b, s| b * s),
+            BlendMode::Screen => separable(screen),
+            BlendMode::Overlay => separable(|b, s| hard_light(s, b)),
+            BlendMode::Darken => separable(f32::min),
+            BlendMode::Lighten => separable(f32::max),
+            BlendMode::ColorDodge => separable(|b, s| {
+                if b == 0.0 {
+                    0.0
+                } else if s >= 1.0 {
+                    1.0
+                } else {
+                    (b / (1.0 - s)).min(1.0)
+                }
+            }),
+            BlendMode::ColorBurn => separable(|b, s| {
+                if b >= 1.0 {
+                    1.0
+                } else if s <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - b) / s).min(1.0)
+                }
+            }),
+            BlendMode::HardLight => separable(hard_light),
+            BlendMode::SoftLight => separable(|b, s| {
+                if s <= 0.5 {
+                    b - (1.0 - 2.0 * s) * b * (1.0 - b)
+                } else {
+                    let d = if b <= 0.25 {
+                        ((16.0 * b - 12.0) * b + 4.0) * b
+                    } else {
+                        b.sqrt()
+                    };
+                    b + (2.0 * s - 1.0) * (d - b)
+                }
+            }),
+            BlendMode::Difference => separable(|b, s| (b - s).abs()),
+            BlendMode::Exclusion => separable(|b, s| b + s - 2.0 * b * s),
+            BlendMode::Hue => set_lum(set_sat(s, sat(b)), lum(b)),
+            BlendMode::Saturation => set_lum(set_sat(b, sat(s)), lum(b)),
+            BlendMode::Color => set_lum(s, lum(b)),
+            BlendMode::Luminosity => set_lum(b, lum(s)),
+        }
+    }
+}
+
+/// The `Screen` blend function of a single channel.
+fn screen(b: f32, s: f32) -> f32 {
+    b + s - b * s
+}
+
+/// The `HardLight` blend function of a single channel.
+fn hard_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b * 2.0 * s
+    } else {
+        screen(b, 2.0 * s - 1.0)
+    }
+}
+
+/// Returns the luminosity of a color.
+fn lum(c: [f32; 3]) -> f32 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+/// Brings the channels of a color back between `0.0` and `1.0`, keeping its luminosity.
+fn clip_color(c: [f32; 3]) -> [f32; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    c.map(|channel| {
+        let mut channel = channel;
+        if n < 0.0 {
+            channel = l + (channel - l) * l / (l - n);
+        }
+        if x > 1.0 {
+            channel = l + (channel - l) * (1.0 - l) / (x - l);
+        }
+        channel
+    })
+}
+
+/// Returns the color with its luminosity set to `l`.
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    clip_color(c.map(|channel| channel + d))
+}
+
+/// Returns the saturation of a color.
+fn sat(c: [f32; 3]) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+/// Returns the color with its saturation set to `s`.
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    if max <= min {
+        return [0.0; 3];
+    }
+    c.map(|channel| (channel - min) * s / (max - min))
+}
+
+/// Combines a layer of the same size as the canvas with the canvas.
+///
+/// The colors of the layer are first mixed with the colors of the canvas using `mode`, and
+/// the result is then combined with the canvas using `operator`, both in the color space
+/// that the canvas and the layer blend in.
+pub(crate) fn composite(
+    canvas: &mut Canvas,
+    layer: &Canvas,
+    operator: CompositeOp,
+    mode: BlendMode,
+) {
+    for (destination, source) in canvas.pixels_mut().zip(layer.pixels()) {
+        let (sa, da) = (source[3], destination[3]);
+        let (fs, fd) = operator.factors(sa, da);
+
+        let alpha = sa * fs + da * fd;
+        if alpha <= 0.0 {
+            destination.0 = [0.0; 4];
+            continue;
+        }
+
+        // Blend modes mix colors with their alpha divided out.
+        let s = unpremultiply(*source);
+        let d = unpremultiply(*destination);
+        // Where the canvas is transparent, the component keeps its own color.
+        let mixed = mode.mix(d, s);
+        for channel in 0..3 {
+            let s = (1.0 - da) * s[channel] + da * mixed[channel];
+            destination[channel] = s * sa * fs + d[channel] * da * fd;
+        }
+        destination[3] = alpha;
+    }
+}
+
+/// Divides the alpha out of the color of a premultiplied pixel.
+fn unpremultiply(pixel: Rgba<f32>) -> [f32; 3] {
+    if pixel[3] <= 0.0 {
+        return [0.0; 3];
+    }
+    [pixel[0], pixel[1], pixel[2]].map(|c| c / pixel[3])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Composites a single source pixel over a single destination pixel with the given
+    /// operator and returns the premultiplied result.
+    fn composite_pixel(operator: CompositeOp, source: [u8; 4], destination: [u8; 4]) -> [f32; 4] {
+        let image = |color| RgbaImage::from_pixel(1, 1, Rgba(color));
+        let mut canvas = decode_image(&image(destination), ColorSpace::Srgb);
+        let layer = decode_image(&image(source), ColorSpace::Srgb);
+        composite(&mut canvas, &layer, operator, BlendMode::Normal);
+        canvas.get_pixel(0, 0).0
+    }
+
+    #[test]
+    fn linear_blending_keeps_precision_until_encoded() {
+        // Many faint layers over black add up to the same gray as computed at once, instead
+        // of every layer being rounded to 8 bits in sRGB.
+        let space = ColorSpace::LinearLight;
+        let mut pixel = decode_pixel(Rgba([0, 0, 0, 255]), space);
+        for _ in 0..100 {
+            blend(&mut pixel, Rgba([255, 255, 255, 255]), 0.01, space);
+        }
+        let light = 1.0 - 0.99f32.powi(100);
+        let expected = space.encode(light);
+        let [r, g, b, a] = encode_pixel(pixel, space).0;
+        assert_eq!([r, g, b, a], [expected, expected, expected, 255]);
+    }
+
+    #[test]
+    fn porter_duff_operators() {
+        // Red at 60% over blue at 40%, so the premultiplied source is (0.6, 0, 0, 0.6) and
+        // the premultiplied destination is (0, 0, 0.4, 0.4).
+        let source = [255, 0, 0, 153];
+        let destination = [0, 0, 255, 102];
+        let expected = [
+            (CompositeOp::SourceOver, [0.6, 0.0, 0.16, 0.76]),
+            (CompositeOp::SourceIn, [0.24, 0.0, 0.0, 0.24]),
+            (CompositeOp::SourceOut, [0.36, 0.0, 0.0, 0.36]),
+            (CompositeOp::SourceAtop, [0.24, 0.0, 0.16, 0.4]),
+            (CompositeOp::DestinationOver, [0.36, 0.0, 0.4, 0.76]),
+            (CompositeOp::DestinationIn, [0.0, 0.0, 0.24, 0.24]),
+            (CompositeOp::DestinationOut, [0.0, 0.0, 0.16, 0.16]),
+            (CompositeOp::DestinationAtop, [0.36, 0.0, 0.24, 0.6]),
+            (CompositeOp::Xor, [0.36, 0.0, 0.16, 0.52]),
+            (CompositeOp::Copy, [0.6, 0.0, 0.0, 0.6]),
+        ];
+        for (operator, expected) in expected {
+            let result = composite_pixel(operator, source, destination);
+            for (r, e) in result.iter().zip(expected) {
+                assert!(
+                    (r - e).abs() <= 1.0 / 255.0,
+                    "{operator:?}: {result:?} != {expected:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn operators_clear_where_nothing_is_kept() {
+        let transparent = [0, 0, 0, 0];
+        let opaque = [0, 255, 0, 255];
+        assert_eq!(
+            composite_pixel(CompositeOp::SourceIn, opaque, transparent),
+            [0.0; 4]
+        );
+        assert_eq!(
+            composite_pixel(CompositeOp::DestinationIn, transparent, opaque),
+            [0.0; 4]
+        );
+        assert_eq!(composite_pixel(CompositeOp::Xor, opaque, opaque), [0.0; 4]);
+    }
+
+    /// Asserts that every channel of `result` is within rounding error of `expected`.
+    fn assert_close(mode: BlendMode, result: [f32; 3], expected: [f32; 3]) {
+        for (r, e) in result.iter().zip(expected) {
+            assert!((r - e).abs() < 1e-5, "{mode:?}: {result:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn blend_modes() {
+        // Expected values worked out by hand from the formulas of the blend functions in the
+        // W3C Compositing and Blending spec.
+        let backdrop = [0.2, 0.4, 0.8];
+        let source = [0.6, 0.3, 0.1];
+        let expected = [
+            (BlendMode::Normal, [0.6, 0.3, 0.1]),
+            (BlendMode::Multiply, [0.12, 0.12, 0.08]),
+            (BlendMode::Screen, [0.68, 0.58, 0.82]),
+            (BlendMode::Overlay, [0.24, 0.24, 0.64]),
+            (BlendMode::Darken, [0.2, 0.3, 0.1]),
+            (BlendMode::Lighten, [0.6, 0.4, 0.8]),
+            (BlendMode::ColorDodge, [0.5, 0.571429, 0.888889]),
+            (BlendMode::ColorBurn, [0.0, 0.0, 0.0]),
+            (BlendMode::HardLight, [0.36, 0.24, 0.16]),
+            (BlendMode::SoftLight, [0.2496, 0.304, 0.672]),
+            (BlendMode::Difference, [0.4, 0.1, 0.7]),
+            (BlendMode::Exclusion, [0.56, 0.46, 0.74]),
+            (BlendMode::Hue, [0.6624, 0.3024, 0.0624]),
+            (BlendMode::Saturation, [0.230667, 0.397333, 0.730667]),
+            (BlendMode::Color, [0.616, 0.316, 0.116]),
+            (BlendMode::Luminosity, [0.184, 0.384, 0.784]),
+        ];
+        for (mode, expected) in expected {
+            assert_close(mode, mode.mix(backdrop, source), expected);
+        }
+
+        let burn = BlendMode::ColorBurn;
+        assert_close(
+            burn,
+            burn.mix([0.2, 0.6, 0.9], [0.5, 0.8, 0.3]),
+            [0.0, 0.5, 0.666667],
+        );
+    }
+
+    #[test]
+    fn dodge_and_burn_limits() {
+        let dodge = BlendMode::ColorDodge;
+        assert_close(
+            dodge,
+            dodge.mix([0.0, 0.5, 0.9], [1.0, 1.0, 0.5]),
+            [0.0, 1.0, 1.0],
+        );
+        let burn = BlendMode::ColorBurn;
+        assert_close(
+            burn,
+            burn.mix([1.0, 0.5, 0.1], [0.0, 0.0, 0.5]),
+            [1.0, 0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn non_separable_modes_keep_gray_gray() {
+        let gray = [0.5; 3];
+        let color = [0.9, 0.2, 0.4];
+        assert_close(
+            BlendMode::Hue,
+            BlendMode::Hue.mix(color, gray),
+            [lum(color); 3],
+        );
+        assert_close(
+            BlendMode::Saturation,
+            BlendMode::Saturation.mix(color, gray),
+            [lum(color); 3],
+        );
+        assert_close(
+            BlendMode::Luminosity,
+            BlendMode::Luminosity.mix(gray, gray),
+            gray,
+        );
+    }
+}
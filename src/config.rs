@@ -1,5 +1,36 @@
+use crate::compositing::ColorSpace;
 use image::Rgba;
 
+/// What happens when a component reaches beyond the edges of the canvas.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{CanvasPolicy, Config, Rgba};
+///
+/// // Fail instead of drawing components that do not fit on the canvas.
+/// let config = Config::new(800, 600, Rgba([255, 255, 255, 255]), None, "output.png", None)
+///     .canvas_policy(CanvasPolicy::Error);
+///
+/// // Report components that are clipped, and draw them anyway.
+/// let config = Config::new(800, 600, Rgba([255, 255, 255, 255]), None, "output.png", None)
+///     .canvas_policy(CanvasPolicy::Warn(|warning| eprintln!("{warning}")));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub enum CanvasPolicy {
+    /// The component is drawn, and the parts outside of the canvas are cut off.
+    #[default]
+    Clip,
+    /// Drawing fails with `CustomError::OutOfCanvas`, and the image is not saved.
+    ///
+    /// Components are checked one at a time as they are drawn, so other components, such
+    /// as earlier children of the same group, may already have been drawn when drawing
+    /// fails. They are only drawn onto a copy of the canvas, which is discarded.
+    Error,
+    /// The component is drawn like with `Clip`, and the function is called with a warning.
+    Warn(fn(&str)),
+}
+
 /// The `Config` struct holds configuration settings for the drawing canvas.
 ///
 /// It allows you to specify various parameters such as the width and height of the canvas,
@@ -24,9 +55,15 @@ use image::Rgba;
 /// - `path`: Path to the canvas image.
 /// - `font_path`: Optional path to the font file.
 ///
+/// The color space and the canvas policy can only be set with the `color_space` and
+/// `canvas_policy` methods, so a `Config` is created with `new` instead of from its fields.
+/// This lets options be added later without breaking existing code.
+///
 /// # Methods
 ///
 /// - `new`: Creates a new `Config` instance with the specified settings.
+/// - `color_space`: Sets the color space in which components are blended with the canvas.
+/// - `canvas_policy`: Sets what happens when a component reaches beyond the canvas.
 ///
 /// # Note
 ///
@@ -42,6 +79,8 @@ pub struct Config {
     pub border: Option<Rgba<u8>>,
     pub path: &'static str,
     pub font_path: Option<&'static str>,
+    pub(crate) color_space: ColorSpace,
+    pub(crate) canvas_policy: CanvasPolicy,
 }
 
 impl Config {
@@ -58,7 +97,8 @@ impl Config {
     ///
     /// # Returns
     ///
-    /// A `Config` instance with the specified settings.
+    /// A `Config` instance with the specified settings, blending in sRGB and clipping
+    /// components at the edges of the canvas.
     pub fn new(
         width: u32,
         height: u32,
@@ -74,6 +114,26 @@ impl Config {
             border,
             path,
             font_path,
+            color_space: ColorSpace::Srgb,
+            canvas_policy: CanvasPolicy::Clip,
         }
     }
+
+    /// Sets the color space in which components are blended with the canvas.
+    ///
+    /// Blending in `ColorSpace::LinearLight` gives smoother anti-aliased edges and cleaner
+    /// mixes of translucent colors.
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Sets what happens when a component reaches beyond the canvas.
+    ///
+    /// By default, components are clipped at the edges of the canvas, so they can be
+    /// placed partially off the canvas, e.g. at negative coordinates.
+    pub fn canvas_policy(mut self, canvas_policy: CanvasPolicy) -> Self {
+        self.canvas_policy = canvas_policy;
+        self
+    }
 }
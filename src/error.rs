@@ -11,24 +11,29 @@ pub enum CustomError {
     ThereIsNoComponent,
     /// Error indicating that no font is provided.
     NoFontProvided,
+    /// Error indicating that the font data could not be parsed.
+    InvalidFont,
 }
 
 impl std::fmt::Display for CustomError {
     /// Implements the Display trait to format the error messages.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            &CustomError::OutOfCanvas => {
+            CustomError::OutOfCanvas => {
                 write!(f, "ERROR: Out of canvas")
             }
-            &CustomError::NoConfigProvided => {
+            CustomError::NoConfigProvided => {
                 write!(f, "ERROR: No config provided")
             }
-            &CustomError::ThereIsNoComponent => {
+            CustomError::ThereIsNoComponent => {
                 write!(f, "ERROR: There is no component")
             }
-            &CustomError::NoFontProvided => {
+            CustomError::NoFontProvided => {
                 write!(f, "ERROR: No font provided")
             }
+            CustomError::InvalidFont => {
+                write!(f, "ERROR: Invalid font")
+            }
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::components::ComponentTrait;
+use crate::compositing::{decode_image, encode_image};
 
 use super::{components::Component, config::Config, error::CustomError};
 use image::{ImageBuffer, Rgba, RgbaImage};
@@ -18,17 +19,18 @@ use std::error::Error;
 /// // Create a new image with a specified configuration
 /// let config = Config::new(800, 600, Rgba([255, 255, 255, 255]), Some(Rgba([0, 0, 0, 255])), "path/to/canvas/image.png", Some("path/to/font.ttf"));
 ///
-/// let image = Image::new_with_config(config);
+/// let mut image = Image::new();
+/// image.config(config);
 ///
 /// // Add drawable components to the image
 /// let components = vec![
-///     Components::Circle(50, 50, 30, Rgba([255, 0, 0, 255])),
-///     Components::Rectangle(40, 60, 10, 20, Rgba([0, 255, 0, 255])),
-///     Components::Line(10, 10, 80, 80, Rgba([0, 0, 255, 255])),
-///     Components::Text(30, 40, 16, "Hello, Rust!", Rgba([255, 255, 255, 255]), Some((Rgba([0, 0, 0, 255]), 2))),
+///     Components::Circle(50.0, 50.0, 30.0, Rgba([255, 0, 0, 255])),
+///     Components::Rectangle(40.0, 60.0, 10.0, 20.0, Rgba([0, 255, 0, 255])),
+///     Components::Line(10.0, 10.0, 80.0, 80.0, Rgba([0, 0, 255, 255])),
+///     Components::Text(30.0, 40.0, 16, "Hello, Rust!", Rgba([255, 255, 255, 255]), Some((Rgba([0, 0, 0, 255]), 2))),
 /// ];
 ///
-/// image.add_components(components);
+/// image.add_components(components.iter().collect());
 /// ```
 ///
 /// # Fields
@@ -58,6 +60,12 @@ pub struct Image<'a> {
     components: Option<Vec<&'a Component>>,
 }
 
+impl<'a> Default for Image<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Image<'a> {
     /// Creates a new Image instance with default values.
     pub fn new() -> Self {
@@ -131,18 +139,23 @@ impl<'a> Image<'a> {
 
     /// Draws all the components on the image and saves it to the specified path.
     ///
+    /// Components are drawn in the order of their z-index, and in the order they were added
+    /// among components with the same z-index.
+    ///
     /// # Errors
     ///
     /// Returns an error if there is no configuration, no components, or an issue occurs during drawing or saving.
     pub fn draw(&self) -> Result<(), Box<dyn Error>> {
         if let Some(components) = &self.components {
             if let Some(config) = &self.config {
-                if let Some(buffer) = self.image_buffer.to_owned().as_mut() {
-                    for component in components {
-                        let c = component;
-                        c.draw(config.to_owned(), buffer)?;
+                if let Some(buffer) = &self.image_buffer {
+                    // Components are drawn onto a working canvas, which is only converted
+                    // back to 8-bit colors once to be saved.
+                    let mut canvas = decode_image(buffer, config.color_space);
+                    for component in Component::drawing_order(components.iter().copied()) {
+                        component.draw(config.to_owned(), &mut canvas)?;
                     }
-                    buffer.save(config.path)?;
+                    encode_image(&canvas, config.color_space).save(config.path)?;
                     Ok(())
                 } else {
                     Err(Box::new(CustomError::NoConfigProvided))
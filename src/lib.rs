@@ -13,7 +13,7 @@
 //! To use `omage`, you can start by creating a `Config` for your canvas, adding drawable components with the `Components` struct,
 //! and rendering the image with the `Image` struct.
 //!
-//! ```rust
+//! ```rust,no_run
 //! use omage::{Config, Components, Image, Rgba};
 //!
 //! // Create a new canvas configuration
@@ -22,15 +22,15 @@
 //!
 //! // Creane new drawable components
 //! let components = vec![
-//!     Components::Circle(50, 50, 30, Rgba([255, 0, 0, 255])),
-//!     Components::Rectangle(40, 60, 10, 20, Rgba([0, 255, 0, 255])),
-//!     Components::Line(10, 10, 80, 80, Rgba([0, 0, 255, 255])),
-//!     Components::Text(30, 40, 16, "Hello, Rust!", Rgba([255, 255, 255, 255]), Some((Rgba([0, 0, 0, 255]), 2))),
+//!     Components::Circle(50.0, 50.0, 30.0, Rgba([255, 0, 0, 255])),
+//!     Components::Rectangle(40.0, 60.0, 10.0, 20.0, Rgba([0, 255, 0, 255])),
+//!     Components::Line(10.0, 10.0, 80.0, 80.0, Rgba([0, 0, 255, 255])),
+//!     Components::Text(30.0, 40.0, 16, "Hello, Rust!", Rgba([255, 255, 255, 255]), Some((Rgba([0, 0, 0, 255]), 2))),
 //! ];
 //!
 //! // Create a new image with the specified configuration
 //! let mut image = Image::new();
-//! image.config(config).init().unwrap().add_components(components).draw().unwrap();
+//! image.config(config).init().unwrap().add_components(components.iter().collect()).draw().unwrap();
 //! ```
 //!
 //! ## Modules
@@ -40,11 +40,13 @@
 //! - `config`: Configuration settings for the drawing canvas.
 //! - `error`: Custom error types for the `omage` crate.
 //! - `images`: Represents an image with configurable settings and drawable components.
+//! - `text`: Styling options for text components, such as wrapping and line spacing.
 //!
 //! ## Example
 //!
 //! ```rust
 //! use omage::colors::*;
+//! use omage::text::{HorizontalAlign, LineJoin, Outline, TextStyle, VerticalAlign};
 //! use omage::{Components, Config, Image, Rgba};
 //!
 //! const HEIGHT: u32 = 100;
@@ -62,18 +64,27 @@
 //!
 //!     let mut image = Image::new();
 //!
-//!     let circle1 = Components::Circle(50, 55, 30, Rgba([255, 0, 0, 200]));
-//!     let circle2 = Components::Circle(75, 55, 30, Rgba([0, 255, 0, 200]));
-//!     let circle3 = Components::Circle(65, 35, 30, Rgba([0, 0, 255, 200]));
-//!
-//!     let text = "OMAGE";
-//!     let text = Components::Text(
-//!         config.width / 2 - 40,
-//!         config.height / 2 - 25,
+//!     let circle1 = Components::Circle(50.0, 55.0, 30.0, Rgba([255, 0, 0, 200]));
+//!     let circle2 = Components::Circle(75.0, 55.0, 30.0, Rgba([0, 255, 0, 200]));
+//!     let circle3 = Components::Circle(65.0, 35.0, 30.0, Rgba([0, 0, 255, 200]));
+//!
+//!     let style = TextStyle {
+//!         outline: Some(Outline {
+//!             color: BLACK,
+//!             width: 3.0,
+//!             join: LineJoin::Round,
+//!         }),
+//!         align: HorizontalAlign::Center,
+//!         vertical_align: VerticalAlign::Middle,
+//!         ..Default::default()
+//!     };
+//!     let text = Components::StyledText(
+//!         config.width as f32 / 2.0,
+//!         config.height as f32 / 2.0,
 //!         50,
-//!         text,
+//!         "OMAGE",
 //!         Rgba([255, 255, 255, 255]),
-//!         Some((BLACK, 3)),
+//!         style,
 //!     );
 //!
 //!     image
@@ -91,14 +102,27 @@
 //! This crate is licensed under the MIT License - see the [LICENSE](https://opensource.org/licenses/MIT) file for details.
 //!
 
+mod clip;
 /// Constants for common RGBA colors.
 pub mod colors;
 mod components;
+mod compositing;
 mod config;
 mod error;
 mod images;
+mod mask;
+mod path;
+mod soft_mask;
+mod sprite;
+/// Styling options for text components.
+pub mod text;
+mod transform;
 
-pub use components::Components;
-pub use config::Config;
-pub use image::Rgba;
+pub use clip::{Clip, PathCommand};
+pub use components::{Component, Components};
+pub use compositing::{BlendMode, ColorSpace, CompositeOp};
+pub use config::{CanvasPolicy, Config};
+pub use image::{GrayImage, Rgba, RgbaImage};
 pub use images::Image;
+pub use soft_mask::{MaskMode, SoftMask};
+pub use transform::Transform;
@@ -0,0 +1,275 @@
+use crate::compositing::{blend, Canvas, ColorSpace};
+use crate::text::LineJoin;
+use image::Rgba;
+
+/// A coverage mask covering a rectangular region of the canvas.
+///
+/// Every pixel of the mask holds a coverage value between `0.0` (not covered) and `1.0`
+/// (fully covered), which is used to scale the alpha of a color when the mask is drawn.
+#[derive(Clone)]
+pub(crate) struct Mask {
+    /// X-coordinate of the top-left corner of the mask on the canvas.
+    x: i32,
+    /// Y-coordinate of the top-left corner of the mask on the canvas.
+    y: i32,
+    /// Width of the mask.
+    width: u32,
+    /// Height of the mask.
+    height: u32,
+    /// Coverage values, row by row.
+    data: Vec<f32>,
+}
+
+impl Mask {
+    /// Creates an empty mask with its top-left corner at `(x, y)`.
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            // Multiplied as `usize`, so that the size of huge masks does not wrap around.
+            data: vec![0.0; width as usize * height as usize],
+        }
+    }
+
+    /// Returns the coverage at the canvas position `(x, y)`, or `0.0` outside of the mask.
+    pub fn get(&self, x: i32, y: i32) -> f32 {
+        let (mx, my) = (x - self.x, y - self.y);
+        if mx < 0 || my < 0 || mx >= self.width as i32 || my >= self.height as i32 {
+            return 0.0;
+        }
+        self.data[(my as u32 * self.width + mx as u32) as usize]
+    }
+
+    /// Adds coverage at the canvas position `(x, y)`, saturating at `1.0`.
+    pub fn add(&mut self, x: i32, y: i32, coverage: f32) {
+        let (mx, my) = (x - self.x, y - self.y);
+        if mx < 0 || my < 0 || mx >= self.width as i32 || my >= self.height as i32 {
+            return;
+        }
+        let value = &mut self.data[(my as u32 * self.width + mx as u32) as usize];
+        *value = (*value + coverage).min(1.0);
+    }
+
+    /// Returns whether all covered pixels lie on a canvas of the given size.
+    ///
+    /// Pixels covered by less than one step of an 8-bit alpha channel do not count, as they
+    /// would not show and are often only rounding errors of the rasterizer.
+    pub fn within(&self, width: u32, height: u32) -> bool {
+        (0..self.height).all(|my| {
+            (0..self.width).all(|mx| {
+                let (x, y) = (self.x + mx as i32, self.y + my as i32);
+                self.data[(my * self.width + mx) as usize] < 1.0 / 255.0
+                    || (x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height)
+            })
+        })
+    }
+
+    /// Returns a mask covering both masks, with the larger coverage of the two at every pixel.
+    pub fn union(&self, other: &Mask) -> Mask {
+        let (left, top) = (self.x.min(other.x), self.y.min(other.y));
+        let right = (self.x + self.width as i32).max(other.x + other.width as i32);
+        let bottom = (self.y + self.height as i32).max(other.y + other.height as i32);
+
+        let mut union = Mask::new(left, top, (right - left) as u32, (bottom - top) as u32);
+        for my in 0..union.height as i32 {
+            for mx in 0..union.width as i32 {
+                let (x, y) = (left + mx, top + my);
+                union.data[(my as u32 * union.width + mx as u32) as usize] =
+                    self.get(x, y).max(other.get(x, y));
+            }
+        }
+        union
+    }
+
+    /// Returns a copy of the mask grown by `radius` pixels in every direction.
+    ///
+    /// The shape of the grown corners depends on `join`: round joins dilate with a disc,
+    /// miter joins with a square and bevel joins with a diamond. Fractional radii cover the
+    /// outermost pixels partially.
+    pub fn dilate(&self, radius: f32, join: LineJoin) -> Mask {
+        let radius = radius.max(0.0);
+        let r = radius.ceil() as i32;
+        let mut offsets = Vec::new();
+        for oy in -r..=r {
+            for ox in -r..=r {
+                let weight = match join {
+                    LineJoin::Round => {
+                        let distance = ((ox * ox + oy * oy) as f32).sqrt();
+                        (radius + 0.5 - distance).clamp(0.0, 1.0)
+                    }
+                    LineJoin::Miter => {
+                        (radius + 1.0 - ox.abs().max(oy.abs()) as f32).clamp(0.0, 1.0)
+                    }
+                    LineJoin::Bevel => {
+                        (radius + 0.5 - (ox.abs() + oy.abs()) as f32).clamp(0.0, 1.0)
+                    }
+                };
+                if weight > 0.0 {
+                    offsets.push((ox, oy, weight));
+                }
+            }
+        }
+
+        let mut dilated = Mask::new(
+            self.x - r,
+            self.y - r,
+            self.width + 2 * r as u32,
+            self.height + 2 * r as u32,
+        );
+        for my in 0..dilated.height as i32 {
+            for mx in 0..dilated.width as i32 {
+                let (x, y) = (dilated.x + mx, dilated.y + my);
+                let coverage = offsets
+                    .iter()
+                    .map(|&(ox, oy, weight)| self.get(x + ox, y + oy) * weight)
+                    .fold(0.0, f32::max);
+                dilated.data[(my as u32 * dilated.width + mx as u32) as usize] = coverage;
+            }
+        }
+        dilated
+    }
+
+    /// Returns a copy of the mask moved by `(dx, dy)` pixels.
+    ///
+    /// Masks moved by a fraction of a pixel spread the coverage of every pixel over the
+    /// pixels it lands on.
+    pub fn translate(&self, dx: f32, dy: f32) -> Mask {
+        let (x, y) = (dx.floor(), dy.floor());
+        let (fx, fy) = (dx - x, dy - y);
+        let (x, y) = (self.x + x as i32, self.y + y as i32);
+        if fx == 0.0 && fy == 0.0 {
+            return Mask {
+                x,
+                y,
+                ..self.clone()
+            };
+        }
+
+        let mut moved = Mask::new(x, y, self.width + 1, self.height + 1);
+        for my in 0..moved.height as i32 {
+            for mx in 0..moved.width as i32 {
+                // The pixel is covered by the pixel of the mask that lands on it, and by
+                // the pixels left of and above that one.
+                let (sx, sy) = (self.x + mx, self.y + my);
+                let coverage = self.get(sx, sy) * (1.0 - fx) * (1.0 - fy)
+                    + self.get(sx - 1, sy) * fx * (1.0 - fy)
+                    + self.get(sx, sy - 1) * (1.0 - fx) * fy
+                    + self.get(sx - 1, sy - 1) * fx * fy;
+                moved.data[(my as u32 * moved.width + mx as u32) as usize] = coverage;
+            }
+        }
+        moved
+    }
+
+    /// Returns a copy of the mask blurred with a Gaussian blur of the given radius.
+    ///
+    /// The blurred mask is grown by `radius` pixels in every direction so that the blur
+    /// is not cut off at the edges.
+    pub fn blur(&self, radius: u32) -> Mask {
+        if radius == 0 {
+            return self.clone();
+        }
+
+        let r = radius as i32;
+        let sigma = radius as f32 / 2.0;
+        let kernel: Vec<f32> = (-r..=r)
+            .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let sum: f32 = kernel.iter().sum();
+        let kernel: Vec<f32> = kernel.iter().map(|k| k / sum).collect();
+
+        // The Gaussian kernel is separable, so blur horizontally and then vertically.
+        let mut horizontal = Mask::new(self.x - r, self.y, self.width + 2 * radius, self.height);
+        for my in 0..horizontal.height as i32 {
+            for mx in 0..horizontal.width as i32 {
+                let (x, y) = (horizontal.x + mx, horizontal.y + my);
+                let coverage = (-r..=r)
+                    .map(|i| self.get(x + i, y) * kernel[(i + r) as usize])
+                    .sum();
+                horizontal.data[(my as u32 * horizontal.width + mx as u32) as usize] = coverage;
+            }
+        }
+
+        let mut blurred = Mask::new(
+            horizontal.x,
+            horizontal.y - r,
+            horizontal.width,
+            horizontal.height + 2 * radius,
+        );
+        for my in 0..blurred.height as i32 {
+            for mx in 0..blurred.width as i32 {
+                let (x, y) = (blurred.x + mx, blurred.y + my);
+                let coverage = (-r..=r)
+                    .map(|i| horizontal.get(x, y + i) * kernel[(i + r) as usize])
+                    .sum();
+                blurred.data[(my as u32 * blurred.width + mx as u32) as usize] = coverage;
+            }
+        }
+        blurred
+    }
+
+    /// Draws `color` onto the canvas wherever the mask is covered, blending in `space`.
+    pub fn draw(&self, canvas: &mut Canvas, color: Rgba<u8>, space: ColorSpace) {
+        for my in 0..self.height {
+            for mx in 0..self.width {
+                let coverage = self.data[(my * self.width + mx) as usize];
+                let (x, y) = (self.x + mx as i32, self.y + my as i32);
+                if coverage <= 0.0
+                    || x < 0
+                    || y < 0
+                    || x as u32 >= canvas.width()
+                    || y as u32 >= canvas.height()
+                {
+                    continue;
+                }
+                blend(
+                    canvas.get_pixel_mut(x as u32, y as u32),
+                    color,
+                    coverage,
+                    space,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a mask covering the single pixel at `(x, y)`.
+    fn pixel(x: i32, y: i32) -> Mask {
+        let mut mask = Mask::new(x, y, 1, 1);
+        mask.add(x, y, 1.0);
+        mask
+    }
+
+    #[test]
+    fn translates_by_fractions_of_a_pixel() {
+        let moved = pixel(2, 3).translate(1.0, -1.0);
+        assert_eq!((moved.get(3, 2), moved.get(2, 3)), (1.0, 0.0));
+
+        let moved = pixel(2, 3).translate(0.5, 0.25);
+        assert_eq!(moved.get(2, 3), 0.375);
+        assert_eq!(moved.get(3, 3), 0.375);
+        assert_eq!(moved.get(2, 4), 0.125);
+        assert_eq!(moved.get(3, 4), 0.125);
+    }
+
+    #[test]
+    fn dilates_by_fractional_radii() {
+        let grown = pixel(5, 5).dilate(1.0, LineJoin::Miter);
+        assert_eq!(
+            (grown.get(4, 4), grown.get(6, 5), grown.get(7, 5)),
+            (1.0, 1.0, 0.0)
+        );
+
+        let grown = pixel(5, 5).dilate(1.5, LineJoin::Miter);
+        assert_eq!(
+            (grown.get(6, 6), grown.get(7, 5), grown.get(8, 5)),
+            (1.0, 0.5, 0.0)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::mask::Mask;
+use crate::transform::Transform;
+use ab_glyph_rasterizer::{point, Point, Rasterizer};
+
+/// A filled path made of straight and curved segments in canvas coordinates.
+///
+/// Points added to the path are mapped through its current transform, which makes it
+/// possible to place, rotate and scale outlines while they are being built. Curves are
+/// flattened into lines as they are added.
+#[derive(Debug, Clone)]
+pub(crate) struct Path {
+    /// Lines of the path.
+    lines: Vec<(Point, Point)>,
+    /// Transform applied to points as they are added.
+    transform: Transform,
+    /// First point of the current contour.
+    start: Point,
+    /// Last point of the current contour.
+    current: Point,
+}
+
+impl Path {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            transform: Transform::identity(),
+            start: point(0.0, 0.0),
+            current: point(0.0, 0.0),
+        }
+    }
+
+    /// Sets the transform applied to the points added from now on.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    /// Adds a rectangle with its top-left corner at `(x, y)` to the path.
+    pub fn add_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        use rusttype::OutlineBuilder;
+
+        self.move_to(x, y);
+        self.line_to(x + width, y);
+        self.line_to(x + width, y + height);
+        self.line_to(x, y + height);
+        self.close();
+    }
+
+    /// Adds a rectangle with its top-left corner at `(x, y)` and rounded corners to the path.
+    ///
+    /// The radius is limited to half the width and height of the rectangle.
+    pub fn add_rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32) {
+        use rusttype::OutlineBuilder;
+
+        let r = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+        if r == 0.0 {
+            return self.add_rect(x, y, width, height);
+        }
+        // Distance of the control points of a cubic Bézier curve approximating a quarter circle.
+        let k = r * 0.552_284_8;
+        let (right, bottom) = (x + width, y + height);
+
+        self.move_to(x + r, y);
+        self.line_to(right - r, y);
+        self.curve_to(right - r + k, y, right, y + r - k, right, y + r);
+        self.line_to(right, bottom - r);
+        self.curve_to(
+            right,
+            bottom - r + k,
+            right - r + k,
+            bottom,
+            right - r,
+            bottom,
+        );
+        self.line_to(x + r, bottom);
+        self.curve_to(x + r - k, bottom, x, bottom - r + k, x, bottom - r);
+        self.line_to(x, y + r);
+        self.curve_to(x, y + r - k, x + r - k, y, x + r, y);
+        self.close();
+    }
+
+    /// Adds a circle with its center at `(cx, cy)` to the path.
+    pub fn add_circle(&mut self, cx: f32, cy: f32, r: f32) {
+        use rusttype::OutlineBuilder;
+
+        // Distance of the control points of a cubic Bézier curve approximating a quarter circle.
+        let k = r * 0.552_284_8;
+
+        self.move_to(cx + r, cy);
+        self.curve_to(cx + r, cy + k, cx + k, cy + r, cx, cy + r);
+        self.curve_to(cx - k, cy + r, cx - r, cy + k, cx - r, cy);
+        self.curve_to(cx - r, cy - k, cx - k, cy - r, cx, cy - r);
+        self.curve_to(cx + k, cy - r, cx + r, cy - k, cx + r, cy);
+        self.close();
+    }
+
+    /// Maps a point through the current transform.
+    fn map(&self, x: f32, y: f32) -> Point {
+        let (x, y) = self.transform.apply(x, y);
+        point(x, y)
+    }
+
+    /// Returns the bounds of the path as `(left, top, right, bottom)`, or `None` if the path
+    /// is empty.
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let points = self.lines.iter().flat_map(|&(p0, p1)| [p0, p1]);
+        let (mut left, mut top, mut right, mut bottom) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for p in points {
+            left = left.min(p.x);
+            top = top.min(p.y);
+            right = right.max(p.x);
+            bottom = bottom.max(p.y);
+        }
+        (!self.lines.is_empty()).then_some((left, top, right, bottom))
+    }
+
+    /// Rasterizes the path into a coverage mask using the non-zero fill rule.
+    ///
+    /// Only the part of the path on a canvas of the given size, grown by `margin` pixels in
+    /// every direction, is rasterized, so that huge shapes reaching far beyond the canvas
+    /// stay cheap to draw. Returns `None` if that part of the path is empty.
+    pub fn rasterize(&self, width: u32, height: u32, margin: u32) -> Option<Mask> {
+        let (left, top, right, bottom) = self.bounds()?;
+        let margin = margin as f32;
+        // One extra column and row take the coverage of edges on the right and bottom.
+        let x0 = left.floor().max(-margin);
+        let y0 = top.floor().max(-margin);
+        let x1 = (right.ceil() + 1.0).min(width as f32 + margin);
+        let y1 = (bottom.ceil() + 1.0).min(height as f32 + margin);
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        let (columns, rows) = ((x1 - x0) as usize, (y1 - y0) as usize);
+
+        // Parts beyond the right edge are moved onto an extra column, which is left out of the
+        // mask, so that the last column is still covered by them.
+        let mut rasterizer = Rasterizer::new(columns + 1, rows);
+        // Close the last contour if it was left open.
+        let closing = (self.current != self.start).then_some((self.current, self.start));
+        for (p0, p1) in self.lines.iter().copied().chain(closing) {
+            let (p0, p1) = (point(p0.x - x0, p0.y - y0), point(p1.x - x0, p1.y - y0));
+            // Rows are clipped by the rasterizer, but columns are not.
+            clamp_line(p0, p1, 0.0, columns as f32, |p0, p1| {
+                rasterizer.draw_line(p0, p1)
+            });
+        }
+
+        let mut mask = Mask::new(x0 as i32, y0 as i32, columns as u32, rows as u32);
+        rasterizer.for_each_pixel_2d(|x, y, coverage| {
+            mask.add(x0 as i32 + x as i32, y0 as i32 + y as i32, coverage)
+        });
+        Some(mask)
+    }
+
+    /// Adds a line from the last point to `p`.
+    fn add_line(&mut self, p: Point) {
+        self.lines.push((self.current, p));
+        self.current = p;
+    }
+
+    /// Adds a cubic Bézier curve from `p0` to `p3` as lines, halving it until the halves are
+    /// flat enough, the same way as `Rasterizer::draw_cubic`.
+    fn add_cubic(&mut self, p0: Point, p1: Point, p2: Point, p3: Point, depth: u8) {
+        const FLATNESS_SQUARED: f32 = 0.35 * 0.35;
+        const MAX_DEPTH: u8 = 16;
+
+        let long = distance(p0, p1) + distance(p1, p2) + distance(p2, p3);
+        let short = distance(p0, p3);
+        if depth < MAX_DEPTH && long * long - short * short > FLATNESS_SQUARED {
+            let (p01, p12, p23) = (lerp(0.5, p0, p1), lerp(0.5, p1, p2), lerp(0.5, p2, p3));
+            let (pa, pb) = (lerp(0.5, p01, p12), lerp(0.5, p12, p23));
+            let middle = lerp(0.5, pa, pb);
+            self.add_cubic(p0, p01, pa, middle, depth + 1);
+            self.add_cubic(middle, pb, p23, p3, depth + 1);
+        } else {
+            self.add_line(p3);
+        }
+    }
+}
+
+/// Returns the distance between two points.
+fn distance(p0: Point, p1: Point) -> f32 {
+    (p1.x - p0.x).hypot(p1.y - p0.y)
+}
+
+/// Returns the point at `t` on the line from `p0` to `p1`.
+fn lerp(t: f32, p0: Point, p1: Point) -> Point {
+    point(p0.x + t * (p1.x - p0.x), p0.y + t * (p1.y - p0.y))
+}
+
+/// Splits a line where it crosses `x = min` and `x = max`, and moves the parts outside of
+/// them onto these edges, passing every part to `draw`.
+///
+/// Coverage accumulates along each row from left to right, so a part moved onto the left
+/// edge covers the pixels right of it just as much as the original part did.
+fn clamp_line(p0: Point, p1: Point, min: f32, max: f32, mut draw: impl FnMut(Point, Point)) {
+    let mut splits = vec![0.0, 1.0];
+    for edge in [min, max] {
+        if (p0.x - edge) * (p1.x - edge) < 0.0 {
+            splits.push((edge - p0.x) / (p1.x - p0.x));
+        }
+    }
+    splits.sort_by(f32::total_cmp);
+    let at = |t: f32| {
+        let p = lerp(t, p0, p1);
+        point(p.x.clamp(min, max), p.y)
+    };
+    for pair in splits.windows(2) {
+        draw(at(pair[0]), at(pair[1]));
+    }
+}
+
+impl rusttype::OutlineBuilder for Path {
+    fn move_to(&mut self, x: f32, y: f32) {
+        // Some outlines, e.g. of CFF fonts, leave contours open, so close them implicitly.
+        self.close();
+        self.start = self.map(x, y);
+        self.current = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.map(x, y);
+        self.add_line(p);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        // Quadratic curves are split into lines the same way as by `Rasterizer::draw_quad`.
+        let (p0, p1, p2) = (self.current, self.map(x1, y1), self.map(x, y));
+        let (dx, dy) = (p0.x - 2.0 * p1.x + p2.x, p0.y - 2.0 * p1.y + p2.y);
+        let deviation = dx * dx + dy * dy;
+        let n = if deviation < 0.333 {
+            1
+        } else {
+            1 + (3.0 * deviation).sqrt().sqrt().floor() as usize
+        };
+        for i in 1..n {
+            let t = i as f32 / n as f32;
+            self.add_line(lerp(t, lerp(t, p0, p1), lerp(t, p1, p2)));
+        }
+        self.add_line(p2);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p1, p2, p) = (self.map(x1, y1), self.map(x2, y2), self.map(x, y));
+        self.add_cubic(self.current, p1, p2, p, 0);
+    }
+
+    fn close(&mut self) {
+        if self.current != self.start {
+            self.add_line(self.start);
+        }
+        self.current = self.start;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusttype::OutlineBuilder;
+
+    #[test]
+    fn huge_paths_are_rasterized_only_around_the_canvas() {
+        // Allocating the whole rectangle would take gigabytes.
+        let mut path = Path::new();
+        path.add_rect(-35000.0, -35000.0, 70000.0, 70000.0);
+        let mask = path.rasterize(100, 100, 1).unwrap();
+        for (x, y) in [(0, 0), (50, 50), (99, 99), (-1, 50), (100, 50), (50, 100)] {
+            assert_eq!(mask.get(x, y), 1.0, "({x}, {y})");
+        }
+        assert_eq!(mask.get(-2, 50), 0.0);
+
+        let mut path = Path::new();
+        path.add_circle(1e6, 1e6, 20000.0);
+        assert!(path.rasterize(100, 100, 1).is_none());
+    }
+
+    #[test]
+    fn clipping_keeps_the_coverage_on_the_canvas() {
+        // A triangle reaching far beyond the left and right edges of the canvas.
+        let mut path = Path::new();
+        path.move_to(-500.3, 2.0);
+        path.line_to(600.7, 7.5);
+        path.line_to(20.2, 18.9);
+        path.close();
+        let clipped = path.rasterize(20, 20, 1).unwrap();
+        let whole = path.rasterize(20, 20, 1000).unwrap();
+        for y in 0..20 {
+            for x in 0..20 {
+                assert!(
+                    (clipped.get(x, y) - whole.get(x, y)).abs() < 1e-3,
+                    "({x}, {y})"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::components::Component;
+use crate::compositing::{encode_pixel, Canvas};
+use crate::config::Config;
+use crate::sprite::Sprite;
+use crate::transform::Transform;
+use image::{GrayImage, Rgba, RgbaImage};
+use std::error::Error;
+
+/// How the pixels of a soft mask are turned into the opacity of the masked component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskMode {
+    /// The alpha of the mask is used as opacity.
+    #[default]
+    Alpha,
+    /// The luminance of the mask is used as opacity, so white is opaque and black is
+    /// transparent. Transparent parts of the mask are transparent as well.
+    Luminance,
+}
+
+/// A mask whose pixels set the opacity of a component, e.g. to fade it out or to add a
+/// vignette.
+///
+/// Unlike clips, soft masks can be partially transparent everywhere. Coordinates of a mask
+/// are in the coordinates of the masked component, and move along with its transform.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{Components, MaskMode, Rgba, SoftMask};
+///
+/// // Only the parts of the group below the soft circle stay visible.
+/// let spotlight = Components::Group(vec![
+///     Components::Rectangle(100.0, 100.0, 0.0, 0.0, Rgba([255, 180, 0, 255])),
+/// ])
+/// .mask(
+///     SoftMask::Component(Box::new(Components::Circle(50.0, 50.0, 40.0, Rgba([255, 255, 255, 128])))),
+///     MaskMode::Alpha,
+/// );
+/// ```
+pub enum SoftMask {
+    /// A component, such as a group, rendered on its own.
+    Component(Box<Component>),
+    /// An image with its top-left corner at `(x, y)`.
+    Image {
+        /// The image of the mask.
+        image: RgbaImage,
+        /// X-coordinate of the top-left corner of the image.
+        x: f32,
+        /// Y-coordinate of the top-left corner of the image.
+        y: f32,
+    },
+    /// A grayscale image with its top-left corner at `(x, y)`, e.g. a gradient, whose
+    /// luma is used as opacity in both mask modes.
+    GrayImage {
+        /// The image of the mask.
+        image: GrayImage,
+        /// X-coordinate of the top-left corner of the image.
+        x: f32,
+        /// Y-coordinate of the top-left corner of the image.
+        y: f32,
+    },
+}
+
+impl SoftMask {
+    /// Renders the mask through `transform` and returns the opacity of every pixel of the
+    /// canvas, row by row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component of the mask cannot be drawn.
+    pub(crate) fn opacity(
+        &self,
+        mode: MaskMode,
+        config: Config,
+        width: u32,
+        height: u32,
+        transform: &Transform,
+    ) -> Result<Vec<f32>, Box<dyn Error>> {
+        let mut layer = Canvas::new(width, height);
+        match self {
+            SoftMask::Component(component) => {
+                component.draw_transformed(config, &mut layer, transform)?
+            }
+            SoftMask::Image { image, x, y } => {
+                let placement = Transform::translate(*x, *y).then(transform);
+                Sprite::new(image, placement).draw(&mut layer, config.color_space);
+            }
+            SoftMask::GrayImage { image, x, y } => {
+                // White with the luma as alpha has the same opacity in both modes.
+                let image = RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+                    Rgba([255, 255, 255, image.get_pixel(x, y)[0]])
+                });
+                let placement = Transform::translate(*x, *y).then(transform);
+                Sprite::new(&image, placement).draw(&mut layer, config.color_space);
+            }
+        }
+
+        Ok(layer
+            .pixels()
+            .map(|pixel| {
+                let pixel = encode_pixel(*pixel, config.color_space);
+                let alpha = pixel[3] as f32 / 255.0;
+                match mode {
+                    MaskMode::Alpha => alpha,
+                    MaskMode::Luminance => {
+                        let luminance = 0.2126 * pixel[0] as f32
+                            + 0.7152 * pixel[1] as f32
+                            + 0.0722 * pixel[2] as f32;
+                        luminance / 255.0 * alpha
+                    }
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Components;
+
+    /// Returns the opacity of the mask on a 4×1 canvas.
+    fn opacity(mask: &SoftMask, mode: MaskMode) -> Vec<f32> {
+        let config = Config::new(4, 1, Rgba([0, 0, 0, 0]), None, "output.png", None);
+        mask.opacity(mode, config, 4, 1, &Transform::identity())
+            .unwrap()
+    }
+
+    fn assert_close(actual: Vec<f32>, expected: [f32; 4]) {
+        for (a, b) in actual.iter().zip(expected) {
+            assert!((a - b).abs() < 1.0 / 255.0, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn luminance_mode_makes_dark_pixels_transparent() {
+        let image = RgbaImage::from_fn(3, 1, |x, _| {
+            [
+                Rgba([255, 255, 255, 255]),
+                Rgba([0, 0, 0, 255]),
+                Rgba([255, 255, 255, 128]),
+            ][x as usize]
+        });
+        let mask = SoftMask::Image {
+            image,
+            x: 0.0,
+            y: 0.0,
+        };
+        let half = 128.0 / 255.0;
+        assert_close(opacity(&mask, MaskMode::Alpha), [1.0, 1.0, half, 0.0]);
+        assert_close(opacity(&mask, MaskMode::Luminance), [1.0, 0.0, half, 0.0]);
+    }
+
+    #[test]
+    fn gray_images_have_the_same_opacity_in_both_modes() {
+        let mask = SoftMask::GrayImage {
+            image: GrayImage::from_raw(3, 1, vec![255, 0, 64]).unwrap(),
+            x: 1.0,
+            y: 0.0,
+        };
+        let expected = [0.0, 1.0, 0.0, 64.0 / 255.0];
+        assert_close(opacity(&mask, MaskMode::Alpha), expected);
+        assert_close(opacity(&mask, MaskMode::Luminance), expected);
+    }
+
+    #[test]
+    fn partially_covered_pixels_are_partially_opaque() {
+        // The gray rectangle covers the second pixel by half.
+        let gray = Rgba([128, 128, 128, 255]);
+        let mask = SoftMask::Component(Box::new(Components::Rectangle(1.0, 1.5, 0.0, 0.0, gray)));
+        assert_close(opacity(&mask, MaskMode::Alpha), [1.0, 0.5, 0.0, 0.0]);
+        let luminance = 128.0 / 255.0;
+        assert_close(
+            opacity(&mask, MaskMode::Luminance),
+            [luminance, luminance * 0.5, 0.0, 0.0],
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::compositing::{blend, Canvas, ColorSpace};
+use crate::mask::Mask;
+use crate::transform::Transform;
+use image::{Rgba, RgbaImage};
+
+/// An image placed on the canvas through a transform.
+///
+/// The transform maps the pixel coordinates of the image to the canvas, and the image is
+/// sampled with bilinear filtering wherever it lands between pixels.
+pub(crate) struct Sprite<'a> {
+    /// The image to draw.
+    image: &'a RgbaImage,
+    /// Transform from the coordinates of the image to the canvas.
+    transform: Transform,
+}
+
+impl<'a> Sprite<'a> {
+    /// Creates a sprite drawing `image` through `transform`.
+    pub fn new(image: &'a RgbaImage, transform: Transform) -> Self {
+        Self { image, transform }
+    }
+
+    /// Returns the canvas pixels covered by the sprite as `(x, y, width, height)`, together
+    /// with the transform from the canvas back to the image, or `None` if nothing is covered.
+    fn placement(&self) -> Option<((i32, i32, u32, u32), Transform)> {
+        let inverse = self.transform.invert()?;
+        let (width, height) = (self.image.width() as f32, self.image.height() as f32);
+        if width == 0.0 || height == 0.0 {
+            return None;
+        }
+
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)]
+            .map(|(x, y)| self.transform.apply(x, y));
+        let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min).floor();
+        let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min).floor();
+        let max_x = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max).ceil();
+        let max_y = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max).ceil();
+
+        Some((
+            (
+                min_x as i32,
+                min_y as i32,
+                (max_x - min_x) as u32,
+                (max_y - min_y) as u32,
+            ),
+            inverse,
+        ))
+    }
+
+    /// Samples the image at `(x, y)` in image coordinates with bilinear filtering.
+    ///
+    /// Returns the color with premultiplied alpha, every channel between `0.0` and `1.0`.
+    fn sample(&self, x: f32, y: f32) -> [f32; 4] {
+        // Pixel centers lie at half coordinates.
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let mut color = [0.0; 4];
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - fx) * (1.0 - fy)),
+            (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy),
+            (1, 1, fx * fy),
+        ] {
+            let (px, py) = (x0 as i32 + dx, y0 as i32 + dy);
+            if weight == 0.0
+                || px < 0
+                || py < 0
+                || px as u32 >= self.image.width()
+                || py as u32 >= self.image.height()
+            {
+                continue;
+            }
+            let pixel = self.image.get_pixel(px as u32, py as u32);
+            let alpha = pixel[3] as f32 / 255.0;
+            for (channel, value) in color.iter_mut().take(3).enumerate() {
+                *value += pixel[channel] as f32 / 255.0 * alpha * weight;
+            }
+            color[3] += alpha * weight;
+        }
+        color
+    }
+
+    /// Returns a mask of the alpha channel of the sprite, or `None` if it covers nothing.
+    pub fn mask(&self) -> Option<Mask> {
+        let ((left, top, width, height), inverse) = self.placement()?;
+        let mut mask = Mask::new(left, top, width, height);
+        for y in top..top + height as i32 {
+            for x in left..left + width as i32 {
+                let (u, v) = inverse.apply(x as f32 + 0.5, y as f32 + 0.5);
+                mask.add(x, y, self.sample(u, v)[3]);
+            }
+        }
+        Some(mask)
+    }
+
+    /// Draws the sprite onto the canvas, blending in `space`.
+    pub fn draw(&self, canvas: &mut Canvas, space: ColorSpace) {
+        let ((left, top, width, height), inverse) = match self.placement() {
+            Some(placement) => placement,
+            None => return,
+        };
+        for y in top.max(0)..(top + height as i32).min(canvas.height() as i32) {
+            for x in left.max(0)..(left + width as i32).min(canvas.width() as i32) {
+                let (u, v) = inverse.apply(x as f32 + 0.5, y as f32 + 0.5);
+                let [r, g, b, a] = self.sample(u, v);
+                if a <= 0.0 {
+                    continue;
+                }
+                let color = Rgba([
+                    (r / a * 255.0).round() as u8,
+                    (g / a * 255.0).round() as u8,
+                    (b / a * 255.0).round() as u8,
+                    (a * 255.0).round() as u8,
+                ]);
+                blend(canvas.get_pixel_mut(x as u32, y as u32), color, 1.0, space);
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use rusttype::{point, GlyphId, OutlineBuilder, Rect, VMetrics};
+use std::collections::HashMap;
+
+/// A glyph of a bitmap font.
+struct BitmapGlyph {
+    /// Advance width of the glyph in pixels.
+    advance: i32,
+    /// X-offset of the left edge of the bitmap from the origin of the glyph.
+    x: i32,
+    /// Y-offset of the top edge of the bitmap from the baseline, negative values being above it.
+    y: i32,
+    /// Width of the bitmap.
+    width: u32,
+    /// Height of the bitmap.
+    height: u32,
+    /// Pixels of the bitmap, row by row, `true` for set pixels.
+    pixels: Vec<bool>,
+}
+
+/// A font made of bitmaps, loaded from the Glyph Bitmap Distribution Format (BDF).
+///
+/// Bitmap fonts are drawn at a whole multiple of their native pixel size, with every pixel
+/// of a glyph scaled up to a square block, so that small text stays crisp.
+pub(crate) struct BitmapFont {
+    /// Glyphs of the font, indexed by glyph ID.
+    glyphs: Vec<BitmapGlyph>,
+    /// Glyph IDs of the encoded characters.
+    chars: HashMap<char, GlyphId>,
+    /// Glyph drawn for characters missing from the font.
+    default: Option<GlyphId>,
+    /// Native size of the font in pixels.
+    pixel_size: u32,
+    /// Distance in pixels from the baseline to the top of a line.
+    ascent: i32,
+    /// Distance in pixels from the baseline to the bottom of a line.
+    descent: i32,
+}
+
+/// Parses the whitespace separated integers of a line.
+fn numbers<'a>(words: impl Iterator<Item = &'a str>) -> Option<Vec<i32>> {
+    words.map(|word| word.parse().ok()).collect()
+}
+
+impl BitmapFont {
+    /// Parses a font in the BDF format.
+    ///
+    /// Returns `None` if the source is not a valid BDF font.
+    pub fn parse(source: &str) -> Option<Self> {
+        let mut lines = source.lines().map(str::trim);
+        if !lines.next()?.starts_with("STARTFONT") {
+            return None;
+        }
+
+        let mut bounding_box = None;
+        let (mut pixel_size, mut ascent, mut descent, mut default_char) = (None, None, None, None);
+        let mut glyphs = Vec::new();
+        let mut chars = HashMap::new();
+        let mut default = None;
+
+        while let Some(line) = lines.next() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("FONTBOUNDINGBOX") => match numbers(words)?[..] {
+                    [width, height, x, y] => bounding_box = Some((width, height, x, y)),
+                    _ => return None,
+                },
+                Some("PIXEL_SIZE") => pixel_size = numbers(words)?.first().copied(),
+                Some("FONT_ASCENT") => ascent = numbers(words)?.first().copied(),
+                Some("FONT_DESCENT") => descent = numbers(words)?.first().copied(),
+                Some("DEFAULT_CHAR") => default_char = numbers(words)?.first().copied(),
+                Some("STARTCHAR") => {
+                    let (mut encoding, mut advance, mut bbx) = (-1, None, bounding_box);
+                    let mut pixels = Vec::new();
+                    loop {
+                        let line = lines.next()?;
+                        let mut words = line.split_whitespace();
+                        match words.next() {
+                            Some("ENCODING") => encoding = *numbers(words)?.first()?,
+                            Some("DWIDTH") => advance = numbers(words)?.first().copied(),
+                            Some("BBX") => match numbers(words)?[..] {
+                                [width, height, x, y] => bbx = Some((width, height, x, y)),
+                                _ => return None,
+                            },
+                            Some("BITMAP") => {
+                                let (width, height, _, _) = bbx?;
+                                if width < 0 || height < 0 {
+                                    return None;
+                                }
+                                for _ in 0..height {
+                                    // Every hex digit holds four pixels, leftmost in the highest bit.
+                                    let digits: Vec<u32> = lines
+                                        .next()?
+                                        .chars()
+                                        .map(|digit| digit.to_digit(16))
+                                        .collect::<Option<_>>()?;
+                                    if digits.len() * 4 < width as usize {
+                                        return None;
+                                    }
+                                    pixels.extend((0..width as usize).map(|column| {
+                                        digits[column / 4] & (8 >> (column % 4)) != 0
+                                    }));
+                                }
+                            }
+                            Some("ENDCHAR") => break,
+                            _ => {}
+                        }
+                    }
+
+                    // Sizes come from untrusted files, so they must not overflow.
+                    let (width, height, x, y) = bbx?;
+                    let size = usize::try_from(width.checked_mul(height)?).ok()?;
+                    if pixels.len() != size {
+                        return None;
+                    }
+                    let top = y.checked_add(height)?.checked_neg()?;
+                    let id = GlyphId(u16::try_from(glyphs.len()).ok()?);
+                    if let Some(c) = u32::try_from(encoding).ok().and_then(char::from_u32) {
+                        chars.insert(c, id);
+                    }
+                    if default_char == Some(encoding) {
+                        default = Some(id);
+                    }
+                    glyphs.push(BitmapGlyph {
+                        advance: advance.unwrap_or(width),
+                        x,
+                        y: top,
+                        width: width as u32,
+                        height: height as u32,
+                        pixels,
+                    });
+                }
+                Some("ENDFONT") => break,
+                _ => {}
+            }
+        }
+
+        let (_, box_height, _, box_y) = bounding_box.unwrap_or((0, 0, 0, 0));
+        let ascent = ascent.or(box_height.checked_add(box_y))?;
+        let descent = descent.or(box_y.checked_neg())?;
+        let pixel_size = pixel_size.or(ascent.checked_add(descent))?;
+        if glyphs.is_empty() || pixel_size <= 0 {
+            return None;
+        }
+
+        Some(Self {
+            default,
+            glyphs,
+            chars,
+            pixel_size: pixel_size as u32,
+            ascent,
+            descent,
+        })
+    }
+
+    /// Returns the factor the glyphs are scaled by at the given font size.
+    fn factor(&self, size: f32) -> f32 {
+        (size / self.pixel_size as f32).round().max(1.0)
+    }
+
+    /// Returns the glyph of a glyph ID, or `None` if there is no such glyph.
+    fn glyph(&self, id: GlyphId) -> Option<&BitmapGlyph> {
+        self.glyphs.get(id.0 as usize)
+    }
+
+    /// Returns the ID of the glyph drawn for `c`.
+    pub fn glyph_id(&self, c: char) -> GlyphId {
+        self.chars
+            .get(&c)
+            .copied()
+            .or(self.default)
+            .unwrap_or(GlyphId(u16::MAX))
+    }
+
+    /// Returns the vertical metrics of the font at the given size.
+    pub fn v_metrics(&self, size: f32) -> VMetrics {
+        let factor = self.factor(size);
+        VMetrics {
+            ascent: self.ascent as f32 * factor,
+            descent: -self.descent as f32 * factor,
+            line_gap: 0.0,
+        }
+    }
+
+    /// Returns the advance width of a glyph at the given size.
+    pub fn advance(&self, id: GlyphId, size: f32) -> f32 {
+        self.glyph(id)
+            .map_or(0.0, |glyph| glyph.advance as f32 * self.factor(size))
+    }
+
+    /// Returns the bounding box of a glyph at the given size, relative to its origin.
+    pub fn bounds(&self, id: GlyphId, size: f32) -> Option<Rect<f32>> {
+        let glyph = self.glyph(id)?;
+        if !glyph.pixels.contains(&true) {
+            return None;
+        }
+        let factor = self.factor(size);
+        Some(Rect {
+            min: point(glyph.x as f32 * factor, glyph.y as f32 * factor),
+            max: point(
+                (glyph.x + glyph.width as i32) as f32 * factor,
+                (glyph.y + glyph.height as i32) as f32 * factor,
+            ),
+        })
+    }
+
+    /// Builds the outline of a glyph at the given size, as one rectangle for every run of
+    /// set pixels in a row of the bitmap.
+    pub fn build_outline(&self, id: GlyphId, size: f32, builder: &mut impl OutlineBuilder) {
+        let glyph = match self.glyph(id) {
+            Some(glyph) => glyph,
+            None => return,
+        };
+        let factor = self.factor(size);
+
+        for (row, pixels) in glyph.pixels.chunks(glyph.width.max(1) as usize).enumerate() {
+            let top = (glyph.y + row as i32) as f32 * factor;
+            let bottom = top + factor;
+            let mut column = 0;
+            while column < pixels.len() {
+                if !pixels[column] {
+                    column += 1;
+                    continue;
+                }
+                let start = column;
+                while column < pixels.len() && pixels[column] {
+                    column += 1;
+                }
+                let left = (glyph.x + start as i32) as f32 * factor;
+                let right = (glyph.x + column as i32) as f32 * factor;
+                builder.move_to(left, top);
+                builder.line_to(right, top);
+                builder.line_to(right, bottom);
+                builder.line_to(left, bottom);
+                builder.close();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a font with a single glyph for `A`, whose bitmap has the given rows.
+    fn source(rows: &[&str], end_char: bool) -> String {
+        let mut source = String::from(
+            "STARTFONT 2.1\nFONTBOUNDINGBOX 8 2 0 0\nSTARTCHAR A\nENCODING 65\nDWIDTH 8 0\nBBX 8 2 0 0\nBITMAP\n",
+        );
+        for row in rows {
+            source += row;
+            source += "\n";
+        }
+        if end_char {
+            source += "ENDCHAR\n";
+        }
+        source + "ENDFONT\n"
+    }
+
+    #[test]
+    fn parses_glyph_bitmaps() {
+        let font = BitmapFont::parse(&source(&["A5", "0f"], true)).unwrap();
+        let glyph = font.glyph(font.glyph_id('A')).unwrap();
+        let bits: String = glyph
+            .pixels
+            .iter()
+            .map(|&set| if set { '1' } else { '0' })
+            .collect();
+        assert_eq!(bits, "1010010100001111");
+        assert_eq!((glyph.width, glyph.height, glyph.advance), (8, 2, 8));
+    }
+
+    #[test]
+    fn rejects_a_missing_endchar() {
+        assert!(BitmapFont::parse(&source(&["A5", "0f"], false)).is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_bitmap_rows() {
+        assert!(BitmapFont::parse(&source(&["A5", "0g"], true)).is_none());
+        assert!(BitmapFont::parse(&source(&["A5", "0"], true)).is_none());
+        assert!(BitmapFont::parse(&source(&["A5"], true)).is_none());
+    }
+
+    #[test]
+    fn rejects_glyph_sizes_that_overflow() {
+        for bbx in ["100000 100000 0 0", "-8 -2 0 0", "8 2 0 2147483647"] {
+            let source = source(&["A5", "0f"], true).replace("BBX 8 2 0 0", &format!("BBX {bbx}"));
+            assert!(BitmapFont::parse(&source).is_none(), "{bbx}");
+        }
+    }
+
+    #[test]
+    fn rejects_text_that_is_not_bdf() {
+        assert!(BitmapFont::parse("").is_none());
+        assert!(BitmapFont::parse("STARTPROPERTIES 1\nENDPROPERTIES\n").is_none());
+        assert!(BitmapFont::parse("STARTFONT 2.1\nENDFONT\n").is_none());
+    }
+}
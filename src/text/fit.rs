@@ -0,0 +1,196 @@
+use super::layout::{layout, Run};
+use super::TextStyle;
+
+/// The character appended to text that has been truncated to fit its bounding box.
+const ELLIPSIS: char = '\u{2026}';
+
+/// Resolves the runs to draw for a style with a `fit`.
+///
+/// Returns `runs` and `style` unchanged if the style has no fit or no bounding box.
+/// Otherwise the runs are scaled down together to the largest size at which the text fits
+/// in the bounding box, where the size of the largest run lies between `fit.min_size` and
+/// its original size. If requested and the text still overflows at the minimum size, it is
+/// truncated with an ellipsis.
+pub(crate) fn fit_text<'a>(runs: &[Run<'a>], style: &TextStyle) -> (Vec<Run<'a>>, TextStyle) {
+    let (fit, (width, height)) = match (style.fit, style.bounds) {
+        (Some(fit), Some(bounds)) => (fit, bounds),
+        _ => return (runs.to_vec(), style.clone()),
+    };
+
+    let mut style = style.clone();
+    if fit.wrap {
+        style.max_width = Some(width);
+    }
+    let fits = |runs: &[Run]| {
+        let (w, h) = extents(runs, &style);
+        w <= width && h <= height
+    };
+
+    // Binary search for the largest size that fits.
+    let size = runs.iter().map(|run| run.size).max().unwrap_or(0);
+    let min_size = fit.min_size.min(size);
+    let (mut low, mut high) = (min_size, size);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if fits(&resize(runs, size, mid)) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    let resized = resize(runs, size, low);
+    if !fit.ellipsis || fits(&resized) {
+        return (resized, style);
+    }
+
+    // Binary search for the longest prefix that fits once the ellipsis is appended.
+    let resized = resize(runs, size, min_size);
+    let length: usize = resized.iter().map(|run| run.text.chars().count()).sum();
+    let (mut low_len, mut high_len) = (0, length);
+    while low_len < high_len {
+        let mid = low_len + (high_len - low_len).div_ceil(2);
+        if fits(&truncate(&resized, mid)) {
+            low_len = mid;
+        } else {
+            high_len = mid - 1;
+        }
+    }
+
+    (truncate(&resized, low_len), style)
+}
+
+/// Scales the sizes of the runs so that a run of size `from` becomes size `to`.
+fn resize<'a>(runs: &[Run<'a>], from: u32, to: u32) -> Vec<Run<'a>> {
+    runs.iter()
+        .map(|run| Run {
+            size: (run.size as u64 * to as u64 / from.max(1) as u64).max(1) as u32,
+            ..run.clone()
+        })
+        .collect()
+}
+
+/// Keeps the first `length` characters of the runs and appends an ellipsis.
+fn truncate<'a>(runs: &[Run<'a>], length: usize) -> Vec<Run<'a>> {
+    let mut remaining = length;
+    let mut truncated: Vec<Run> = Vec::new();
+    for run in runs {
+        if remaining == 0 && !truncated.is_empty() {
+            break;
+        }
+        let text: String = run.text.chars().take(remaining).collect();
+        remaining -= text.chars().count();
+        truncated.push(Run {
+            text,
+            ..run.clone()
+        });
+    }
+
+    // Drop trailing whitespace so that the ellipsis follows the last word.
+    let last = truncated.len() - 1;
+    let text = truncated[last].text.trim_end().to_string();
+    truncated[last].text = text;
+    truncated[last].text.push(ELLIPSIS);
+    truncated
+}
+
+/// Returns the width of the widest line and the height of the laid out text.
+fn extents(runs: &[Run], style: &TextStyle) -> (f32, f32) {
+    let layout = layout(runs, (0.0, 0.0), style);
+    let width = layout
+        .lines
+        .iter()
+        .map(|line| line.width)
+        .fold(0.0, f32::max);
+    (width, layout.height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::layout::tests::monospace_font;
+    use crate::text::{Font, TextFit};
+
+    /// Fits `text`, drawn at `size`, in a box of the given size and returns the resulting
+    /// size and text.
+    fn fit(font: &Font, text: &str, size: u32, bounds: (f32, f32), fit: TextFit) -> (u32, String) {
+        let runs = [Run {
+            font,
+            size,
+            text: text.to_string(),
+            bold: false,
+            italic: false,
+        }];
+        let style = TextStyle {
+            bounds: Some(bounds),
+            fit: Some(fit),
+            ..TextStyle::default()
+        };
+        let (runs, _) = fit_text(&runs, &style);
+        (
+            runs[0].size,
+            runs.iter().map(|run| run.text.as_str()).collect(),
+        )
+    }
+
+    const SHRINK: TextFit = TextFit {
+        min_size: 5,
+        wrap: false,
+        ellipsis: false,
+    };
+
+    #[test]
+    fn keeps_the_size_of_text_that_fits() {
+        let font = monospace_font();
+        assert_eq!(
+            fit(&font, "abcd", 20, (100.0, 100.0), SHRINK),
+            (20, "abcd".into())
+        );
+    }
+
+    #[test]
+    fn finds_the_largest_size_that_fits() {
+        // Glyphs are scaled by whole factors, and four glyphs are 20 pixels wide per factor,
+        // so the text fits up to a factor of 2, i.e. below a size of 25.
+        let font = monospace_font();
+        assert_eq!(
+            fit(&font, "abcd", 40, (40.0, 100.0), SHRINK),
+            (24, "abcd".into())
+        );
+    }
+
+    #[test]
+    fn converges_to_the_minimum_size() {
+        let font = monospace_font();
+        assert_eq!(
+            fit(&font, "abcdefgh", 40, (10.0, 100.0), SHRINK),
+            (5, "abcdefgh".into())
+        );
+        assert_eq!(
+            fit(&font, "abcd", 40, (100.0, 1.0), SHRINK),
+            (5, "abcd".into())
+        );
+    }
+
+    #[test]
+    fn truncates_with_an_ellipsis_at_the_minimum_size() {
+        // Every glyph, including the ellipsis, is 5 pixels wide at the minimum size, so
+        // three characters fit in front of the ellipsis.
+        let font = monospace_font();
+        let fit_ellipsis = TextFit {
+            ellipsis: true,
+            ..SHRINK
+        };
+        let (size, text) = fit(&font, "abc defgh", 40, (20.0, 100.0), fit_ellipsis);
+        assert_eq!((size, text.as_str()), (5, "abc\u{2026}"));
+
+        let runs = [Run {
+            font: &font,
+            size,
+            text,
+            bold: false,
+            italic: false,
+        }];
+        assert_eq!(extents(&runs, &TextStyle::default()).0, 20.0);
+    }
+}
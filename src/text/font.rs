@@ -0,0 +1,186 @@
+use super::bitmap::BitmapFont;
+use super::outline::{ColorGlyph, OutlineFont};
+use crate::error::CustomError;
+use rusttype::{GlyphId, OutlineBuilder, Rect, VMetrics};
+use std::error::Error;
+use std::io::Read;
+use std::sync::Arc;
+
+/// A font used to lay out, measure and draw text.
+///
+/// Besides TrueType and OpenType fonts, bitmap fonts in the BDF format are supported for
+/// crisp pixel text at small sizes. Bitmap fonts are drawn at the whole multiple of their
+/// native pixel size nearest to the requested size, with glyphs placed on whole pixels.
+///
+/// Glyphs with color images, such as the emoji of fonts with `CBDT` or `sbix` tables, are
+/// drawn in their own colors instead of the color of their span.
+///
+/// # Examples
+///
+/// ```
+/// use omage::text::Font;
+///
+/// let font = Font::from_file("./fonts/Roboto-Medium.ttf").unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Font {
+    pub(crate) inner: FontKind,
+}
+
+/// The glyphs of a font, either as outlines or as bitmaps.
+#[derive(Clone)]
+pub(crate) enum FontKind {
+    /// A TrueType or OpenType font.
+    Outline(Arc<OutlineFont>),
+    /// A bitmap font.
+    Bitmap(Arc<BitmapFont>),
+}
+
+impl Font {
+    /// Loads a font from a TrueType, OpenType or BDF file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not contain a valid font.
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut file = std::fs::File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Creates a font from the bytes of a TrueType, OpenType or BDF file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes do not contain a valid font.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Box<dyn Error>> {
+        let inner = if bytes.starts_with(b"STARTFONT") {
+            std::str::from_utf8(&bytes)
+                .ok()
+                .and_then(BitmapFont::parse)
+                .map(|font| FontKind::Bitmap(Arc::new(font)))
+        } else {
+            OutlineFont::new(bytes, Vec::new()).map(|font| FontKind::Outline(Arc::new(font)))
+        };
+
+        match inner {
+            Some(inner) => Ok(Self { inner }),
+            None => Err(Box::new(CustomError::InvalidFont)),
+        }
+    }
+
+    /// Returns a copy of the font with a variation axis set to `value`.
+    ///
+    /// Variable fonts define axes such as weight (`"wght"`) and width (`"wdth"`) that can
+    /// be set to any value in their range. Axes that the font does not define are ignored.
+    /// The returned font keeps its own copy of the font data with the axes applied, so
+    /// varied fonts are best created once and reused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use omage::text::Font;
+    ///
+    /// let font = Font::from_file("./fonts/Roboto-Medium.ttf").unwrap();
+    /// let bold = font.with_variation("wght", 700.0).with_variation("wdth", 90.0);
+    /// ```
+    pub fn with_variation(&self, axis: &str, value: f32) -> Font {
+        let tag = ttf_parser::Tag::from_bytes_lossy(axis.as_bytes());
+        match &self.inner {
+            FontKind::Outline(font) => Font {
+                inner: FontKind::Outline(Arc::new(font.with_variation(tag, value))),
+            },
+            FontKind::Bitmap(_) => self.clone(),
+        }
+    }
+
+    /// Returns whether the font is a bitmap font.
+    pub(crate) fn is_bitmap(&self) -> bool {
+        matches!(self.inner, FontKind::Bitmap(_))
+    }
+
+    /// Returns the ID of the glyph drawn for `c`.
+    pub(crate) fn glyph_id(&self, c: char) -> GlyphId {
+        match &self.inner {
+            FontKind::Outline(font) => font.glyph_id(c),
+            FontKind::Bitmap(font) => font.glyph_id(c),
+        }
+    }
+
+    /// Returns the vertical metrics of the font at the given size.
+    pub(crate) fn v_metrics(&self, size: f32) -> VMetrics {
+        match &self.inner {
+            FontKind::Outline(font) => font.v_metrics(size),
+            FontKind::Bitmap(font) => font.v_metrics(size),
+        }
+    }
+
+    /// Returns the advance width of a glyph at the given size.
+    pub(crate) fn advance(&self, id: GlyphId, size: f32) -> f32 {
+        match &self.inner {
+            FontKind::Outline(font) => font.advance(id, size),
+            FontKind::Bitmap(font) => font.advance(id, size),
+        }
+    }
+
+    /// Returns the kerning between two glyphs at the given size.
+    pub(crate) fn kerning(&self, size: f32, first: GlyphId, second: GlyphId) -> f32 {
+        match &self.inner {
+            FontKind::Outline(font) => font.kerning(size, first, second),
+            FontKind::Bitmap(_) => 0.0,
+        }
+    }
+
+    /// Returns the offset of the top of an underline from the baseline and its thickness at
+    /// the given size, or `None` if the font does not define them.
+    pub(crate) fn underline_metrics(&self, size: f32) -> Option<(f32, f32)> {
+        match &self.inner {
+            FontKind::Outline(font) => font.underline_metrics(size),
+            FontKind::Bitmap(_) => None,
+        }
+    }
+
+    /// Returns the offset of the top of a strikethrough line from the baseline and its
+    /// thickness at the given size, or `None` if the font does not define them.
+    pub(crate) fn strikeout_metrics(&self, size: f32) -> Option<(f32, f32)> {
+        match &self.inner {
+            FontKind::Outline(font) => font.strikeout_metrics(size),
+            FontKind::Bitmap(_) => None,
+        }
+    }
+
+    /// Returns the exact bounding box of a glyph at the given size, relative to its origin,
+    /// or `None` if the glyph has neither a color image nor an outline.
+    pub(crate) fn bounds(&self, id: GlyphId, size: f32) -> Option<Rect<f32>> {
+        if let Some(glyph) = self.color_glyph(id, size) {
+            return Some(Rect {
+                min: rusttype::point(glyph.x, glyph.y),
+                max: rusttype::point(
+                    glyph.x + glyph.image.width() as f32,
+                    glyph.y + glyph.image.height() as f32,
+                ),
+            });
+        }
+        match &self.inner {
+            FontKind::Outline(font) => font.bounds(id, size),
+            FontKind::Bitmap(font) => font.bounds(id, size),
+        }
+    }
+
+    /// Builds the outline of a glyph at the given size, with its origin on the baseline.
+    pub(crate) fn build_outline(&self, id: GlyphId, size: f32, builder: &mut impl OutlineBuilder) {
+        match &self.inner {
+            FontKind::Outline(font) => font.build_outline(id, size, builder),
+            FontKind::Bitmap(font) => font.build_outline(id, size, builder),
+        }
+    }
+
+    /// Returns the color image of a glyph at the given size, or `None` if the glyph has none.
+    pub(crate) fn color_glyph(&self, id: GlyphId, size: f32) -> Option<ColorGlyph> {
+        match &self.inner {
+            FontKind::Outline(font) => font.color_glyph(id, size),
+            FontKind::Bitmap(_) => None,
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use super::{Font, HorizontalAlign, TextStyle, VerticalAlign};
+use crate::transform::Transform;
+use rusttype::{point, GlyphId, Point, Rect, VMetrics};
+use std::ops::Range;
+
+#[cfg(feature = "shaping")]
+use super::shaping::BidiText;
+
+/// Angle in degrees by which synthetically slanted glyphs lean to the right.
+const OBLIQUE_ANGLE: f32 = 12.0;
+
+/// A run of text drawn with a single font and size.
+#[derive(Clone)]
+pub(crate) struct Run<'a> {
+    /// Font of the run.
+    pub font: &'a Font,
+    /// Font size of the run.
+    pub size: u32,
+    /// Text content of the run.
+    pub text: String,
+    /// Whether the glyphs of the run are synthetically emboldened.
+    pub bold: bool,
+    /// Whether the glyphs of the run are synthetically slanted.
+    pub italic: bool,
+}
+
+impl Run<'_> {
+    /// Returns the vertical metrics of the run's font at the run's size.
+    pub fn v_metrics(&self) -> VMetrics {
+        self.font.v_metrics(self.size as f32)
+    }
+
+    /// Returns the advance width of a glyph of the run, widened if the run is emboldened.
+    pub fn advance(&self, id: GlyphId) -> f32 {
+        self.font.advance(id, self.size as f32) + 2.0 * self.embolden() as f32
+    }
+
+    /// Returns the bounding box of a glyph of the run relative to its origin, including
+    /// the synthetic emphasis of the run, or `None` if the glyph has no outline.
+    pub fn bounds(&self, id: GlyphId) -> Option<Rect<f32>> {
+        let bounds = self.font.bounds(id, self.size as f32)?;
+        let transform = self.glyph_transform();
+        let corners = [
+            (bounds.min.x, bounds.min.y),
+            (bounds.max.x, bounds.min.y),
+            (bounds.min.x, bounds.max.y),
+            (bounds.max.x, bounds.max.y),
+        ]
+        .map(|(x, y)| transform.apply(x, y));
+
+        let grow = self.embolden() as f32;
+        Some(Rect {
+            min: point(
+                corners.iter().map(|c| c.0).fold(f32::MAX, f32::min) - grow,
+                corners.iter().map(|c| c.1).fold(f32::MAX, f32::min) - grow,
+            ),
+            max: point(
+                corners.iter().map(|c| c.0).fold(f32::MIN, f32::max) + grow,
+                corners.iter().map(|c| c.1).fold(f32::MIN, f32::max) + grow,
+            ),
+        })
+    }
+
+    /// Returns the transform applied to the glyphs of the run before they are placed, which
+    /// slants them if the run is italic and makes room for them to grow if it is bold.
+    pub fn glyph_transform(&self) -> Transform {
+        let slant = if self.italic {
+            Transform::skew(-OBLIQUE_ANGLE, 0.0)
+        } else {
+            Transform::identity()
+        };
+        let grow = self.embolden() as f32;
+        slant.then(&Transform::translate(grow, 0.0))
+    }
+
+    /// Returns the number of pixels the glyphs of the run grow by in every direction when
+    /// they are synthetically emboldened, `0` if the run is not bold.
+    pub fn embolden(&self) -> u32 {
+        if self.bold {
+            (self.size as f32 / 32.0).round().max(1.0) as u32
+        } else {
+            0
+        }
+    }
+}
+
+/// A character of the text, together with the index of the run it belongs to.
+pub(crate) type Item = (char, usize);
+
+/// A laid out glyph.
+pub(crate) struct Glyph {
+    /// ID of the glyph in the font of its run.
+    pub id: GlyphId,
+    /// Position of the origin of the glyph, on the baseline of its line.
+    pub position: Point<f32>,
+    /// Index of the run the glyph belongs to.
+    pub run: usize,
+    /// Advance width of the glyph.
+    pub advance: f32,
+}
+
+/// A single laid out line of text.
+pub(crate) struct Line {
+    /// Glyphs of the line, positioned on the line's baseline.
+    pub glyphs: Vec<Glyph>,
+    /// X-coordinate of the start of the line.
+    pub x: f32,
+    /// Advance width of the line.
+    pub width: f32,
+}
+
+/// Laid out text, made of one or more lines.
+pub(crate) struct Layout {
+    /// Lines of the text, from top to bottom.
+    pub lines: Vec<Line>,
+    /// Y-coordinate of the top of the first line.
+    pub y: f32,
+    /// Height of the text, from the top of the first line to the bottom of the last one.
+    pub height: f32,
+}
+
+/// Without the `shaping` feature lines are laid out in logical order, so nothing needs to be
+/// resolved for the paragraphs of the text.
+#[cfg(not(feature = "shaping"))]
+struct BidiText<'t>(std::marker::PhantomData<&'t str>);
+
+#[cfg(not(feature = "shaping"))]
+impl<'t> BidiText<'t> {
+    fn new(_: &'t str) -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+/// Offsets of text decorations from the baseline, positive values being below it.
+pub(crate) struct Decorations {
+    /// Offset of the top of an underline.
+    pub underline: f32,
+    /// Thickness of an underline.
+    pub underline_thickness: f32,
+    /// Offset of the top of a strikethrough line.
+    pub strikethrough: f32,
+    /// Thickness of a strikethrough line.
+    pub strikethrough_thickness: f32,
+}
+
+impl Decorations {
+    /// Reads the position and thickness of decorations from the font of a run.
+    ///
+    /// Fonts without underline or strikeout metrics get decorations derived from their
+    /// vertical metrics instead. Lines are at least one pixel thick, and decorations of
+    /// bitmap fonts are rounded to whole pixels, like their glyphs.
+    pub fn new(run: &Run) -> Self {
+        let size = run.size as f32;
+        let v_metrics = run.v_metrics();
+        let thickness = (v_metrics.ascent - v_metrics.descent) / 16.0;
+        let (underline, underline_thickness) = run
+            .font
+            .underline_metrics(size)
+            .unwrap_or((-v_metrics.descent * 0.4, thickness));
+        let (strikethrough, strikethrough_thickness) =
+            run.font.strikeout_metrics(size).unwrap_or((
+                -v_metrics.ascent * 0.3 - thickness.max(1.0) / 2.0,
+                thickness,
+            ));
+
+        let decorations = Self {
+            underline,
+            underline_thickness: underline_thickness.max(1.0),
+            strikethrough,
+            strikethrough_thickness: strikethrough_thickness.max(1.0),
+        };
+        if run.font.is_bitmap() {
+            return Self {
+                underline: decorations.underline.round(),
+                underline_thickness: decorations.underline_thickness.round(),
+                strikethrough: decorations.strikethrough.round(),
+                strikethrough_thickness: decorations.strikethrough_thickness.round(),
+            };
+        }
+        decorations
+    }
+}
+
+/// Lays out `runs` at `position`, aligned as described by the style.
+///
+/// The runs are laid out one after another on shared baselines, and every line is as tall as
+/// the largest run on it. Explicit newlines always start a new line. If the style has a
+/// `max_width`, words that would overflow the current line are moved to the next one, and
+/// words wider than `max_width` on their own are broken between characters.
+///
+/// Without a bounding box `position` is the anchor point of the text, otherwise it is the
+/// top-left corner of the bounding box.
+pub(crate) fn layout(runs: &[Run], position: (f32, f32), style: &TextStyle) -> Layout {
+    if runs.is_empty() {
+        return Layout {
+            lines: Vec::new(),
+            y: position.1,
+            height: 0.0,
+        };
+    }
+
+    let items: Vec<Item> = runs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, run)| run.text.chars().map(move |c| (c, i)))
+        .collect();
+    let text: String = items.iter().map(|&(c, _)| c).collect();
+    let bidi = BidiText::new(&text);
+
+    let mut lines = Vec::new();
+    let mut metrics = Vec::new();
+    for (range, fallback) in break_lines(runs, &items, &bidi, style) {
+        let (glyphs, width) = layout_line(runs, &items, range, &bidi, style);
+        metrics.push(line_metrics(runs, &glyphs, fallback));
+        lines.push(Line {
+            glyphs,
+            x: 0.0,
+            width,
+        });
+    }
+
+    // Baselines relative to the top of the first line.
+    let mut baselines = vec![metrics[0].ascent];
+    for pair in metrics.windows(2) {
+        let previous = baselines[baselines.len() - 1];
+        let advance = (pair[0].line_gap - pair[0].descent + pair[1].ascent) * style.line_height;
+        baselines.push(previous + advance);
+    }
+
+    // Extents of the text block relative to the top of the first line.
+    let first_baseline = baselines[0];
+    let last_baseline = baselines[baselines.len() - 1];
+    let height = last_baseline - metrics[metrics.len() - 1].descent;
+
+    let top = match style.bounds {
+        Some((_, box_height)) => {
+            position.1
+                + match style.vertical_align {
+                    VerticalAlign::Top => 0.0,
+                    VerticalAlign::Middle => (box_height - height) / 2.0,
+                    VerticalAlign::Baseline => box_height - last_baseline,
+                    VerticalAlign::Bottom => box_height - height,
+                }
+        }
+        None => {
+            position.1
+                - match style.vertical_align {
+                    VerticalAlign::Top => 0.0,
+                    VerticalAlign::Middle => height / 2.0,
+                    VerticalAlign::Baseline => first_baseline,
+                    VerticalAlign::Bottom => height,
+                }
+        }
+    };
+
+    let lines = lines
+        .into_iter()
+        .zip(baselines)
+        .map(|(mut line, baseline)| {
+            let left = match style.bounds {
+                Some((box_width, _)) => {
+                    position.0
+                        + match style.align {
+                            HorizontalAlign::Left => 0.0,
+                            HorizontalAlign::Center => (box_width - line.width) / 2.0,
+                            HorizontalAlign::Right => box_width - line.width,
+                        }
+                }
+                None => {
+                    position.0
+                        - match style.align {
+                            HorizontalAlign::Left => 0.0,
+                            HorizontalAlign::Center => line.width / 2.0,
+                            HorizontalAlign::Right => line.width,
+                        }
+                }
+            };
+
+            for glyph in line.glyphs.iter_mut() {
+                let p = glyph.position;
+                glyph.position = point(p.x + left, p.y + top + baseline);
+                if runs[glyph.run].font.is_bitmap() {
+                    // Keep the pixels of bitmap glyphs on whole pixels of the canvas.
+                    glyph.position = point(glyph.position.x.round(), glyph.position.y.round());
+                }
+            }
+            line.x = left;
+            line
+        })
+        .collect();
+
+    Layout {
+        lines,
+        y: top,
+        height,
+    }
+}
+
+/// Lays out the line made of the items in `line` on a baseline at `y = 0`, starting at
+/// `x = 0`.
+///
+/// Kerning is applied between neighbouring glyphs of the same run unless it is disabled by
+/// the style, which also controls letter spacing, word spacing and tabular figures. With the
+/// `shaping` feature, lines drawn only with outline fonts are reordered and shaped instead,
+/// using the bidirectional levels resolved for their paragraphs.
+/// Returns the positioned glyphs and the advance width of the line.
+#[cfg_attr(not(feature = "shaping"), allow(unused_variables))]
+fn layout_line(
+    runs: &[Run],
+    items: &[Item],
+    line: Range<usize>,
+    bidi: &BidiText,
+    style: &TextStyle,
+) -> (Vec<Glyph>, f32) {
+    #[cfg(feature = "shaping")]
+    if items[line.clone()]
+        .iter()
+        .all(|&(_, run)| !runs[run].font.is_bitmap())
+    {
+        return super::shaping::shape_line(runs, items, line, bidi, style);
+    }
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    let mut last: Option<(GlyphId, usize)> = None;
+
+    for &(c, run) in &items[line] {
+        let font = runs[run].font;
+        let size = runs[run].size as f32;
+        let id = font.glyph_id(c);
+        if let Some((last_id, last_run)) = last {
+            caret += style.letter_spacing;
+            if style.kerning && last_run == run {
+                caret += font.kerning(size, last_id, id);
+            }
+        }
+        last = Some((id, run));
+
+        let mut advance = runs[run].advance(id);
+        let mut offset = 0.0;
+        if style.tabular_figures && c.is_ascii_digit() {
+            // Center the digit in the width of the widest digit of the font.
+            let figure = ('0'..='9')
+                .map(|d| runs[run].advance(font.glyph_id(d)))
+                .fold(0.0, f32::max);
+            offset = (figure - advance) / 2.0;
+            advance = figure;
+        }
+        if c == ' ' {
+            advance += style.word_spacing;
+        }
+
+        glyphs.push(Glyph {
+            id,
+            position: point(caret + offset, 0.0),
+            run,
+            advance,
+        });
+        caret += advance;
+    }
+
+    (glyphs, caret)
+}
+
+/// Returns the combined vertical metrics of a line, large enough for every run on it.
+///
+/// Empty lines use the metrics of the `fallback` run.
+fn line_metrics(runs: &[Run], glyphs: &[Glyph], fallback: usize) -> VMetrics {
+    let mut used: Vec<usize> = glyphs.iter().map(|glyph| glyph.run).collect();
+    used.dedup();
+    if used.is_empty() {
+        used.push(fallback);
+    }
+
+    used.iter()
+        .map(|&run| runs[run].v_metrics())
+        .reduce(|a, b| VMetrics {
+            ascent: a.ascent.max(b.ascent),
+            descent: a.descent.min(b.descent),
+            line_gap: a.line_gap.max(b.line_gap),
+        })
+        .expect("a line uses at least one run")
+}
+
+/// Measures the advance width of a range of the text.
+fn width(
+    runs: &[Run],
+    items: &[Item],
+    range: Range<usize>,
+    bidi: &BidiText,
+    style: &TextStyle,
+) -> f32 {
+    layout_line(runs, items, range, bidi, style).1
+}
+
+/// Removes trailing spaces from a range of the text.
+fn trim_end(items: &[Item], range: Range<usize>) -> Range<usize> {
+    let mut end = range.end;
+    while end > range.start && items[end - 1].0 == ' ' {
+        end -= 1;
+    }
+    range.start..end
+}
+
+/// Splits the text into lines on explicit newlines and, if needed, wraps it to `max_width`.
+///
+/// Every line is returned as a range of `items`, together with the run whose metrics are
+/// used if the line is empty.
+fn break_lines(
+    runs: &[Run],
+    items: &[Item],
+    bidi: &BidiText,
+    style: &TextStyle,
+) -> Vec<(Range<usize>, usize)> {
+    let mut paragraphs = Vec::new();
+    let (mut start, mut fallback) = (0, 0);
+    for (i, &(c, run)) in items.iter().enumerate() {
+        if c == '\n' {
+            paragraphs.push((start..i, fallback));
+            start = i + 1;
+            fallback = run;
+        }
+    }
+    paragraphs.push((start..items.len(), fallback));
+
+    let max_width = match style.max_width {
+        Some(max_width) => max_width,
+        None => return paragraphs,
+    };
+
+    let mut lines = Vec::new();
+    for (paragraph, fallback) in paragraphs {
+        let mut line = paragraph.start..paragraph.start;
+        let mut word_start = paragraph.start;
+
+        // Every word includes the spaces that follow it.
+        for i in paragraph.clone() {
+            let word_end = i + 1;
+            if word_end < paragraph.end && !(items[i].0 == ' ' && items[word_end].0 != ' ') {
+                continue;
+            }
+            let word = word_start..word_end;
+            word_start = word_end;
+
+            let candidate = line.start..word.end;
+            if width(runs, items, trim_end(items, candidate.clone()), bidi, style) <= max_width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push((trim_end(items, line), fallback));
+            }
+            line = word.clone();
+            if width(runs, items, trim_end(items, word.clone()), bidi, style) > max_width {
+                // Break the word between characters, keeping the remainder on the line.
+                line = word.start..word.start;
+                for j in word {
+                    if j > line.start
+                        && items[j].0 != ' '
+                        && width(runs, items, line.start..j + 1, bidi, style) > max_width
+                    {
+                        lines.push((line.clone(), fallback));
+                        line = j..j;
+                    }
+                    line.end = j + 1;
+                }
+            }
+        }
+        lines.push((trim_end(items, line), fallback));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds a monospace bitmap font with a 10 pixel size in which every printable ASCII
+    /// character and the ellipsis advance by 5 pixels.
+    pub(crate) fn monospace_font() -> Font {
+        let mut source = String::from(
+            "STARTFONT 2.1\nFONTBOUNDINGBOX 5 10 0 -2\nPIXEL_SIZE 10\nFONT_ASCENT 8\nFONT_DESCENT 2\n",
+        );
+        for c in (' '..='~').chain(['\u{2026}']) {
+            source += &format!(
+                "STARTCHAR U+{:04X}\nENCODING {}\nDWIDTH 5 0\nBBX 4 8 0 -2\nBITMAP\n{}ENDCHAR\n",
+                c as u32,
+                c as u32,
+                "F0\n".repeat(8)
+            );
+        }
+        source += "ENDFONT\n";
+        Font::from_bytes(source.into_bytes()).unwrap()
+    }
+
+    /// Breaks `text` into lines and returns the text of every line.
+    fn lines(text: &str, max_width: Option<f32>) -> Vec<String> {
+        let font = monospace_font();
+        let runs = [Run {
+            font: &font,
+            size: 10,
+            text: text.to_string(),
+            bold: false,
+            italic: false,
+        }];
+        let items: Vec<Item> = text.chars().map(|c| (c, 0)).collect();
+        let style = TextStyle {
+            max_width,
+            ..TextStyle::default()
+        };
+        break_lines(&runs, &items, &BidiText::new(text), &style)
+            .into_iter()
+            .map(|(range, _)| items[range].iter().map(|&(c, _)| c).collect())
+            .collect()
+    }
+
+    #[test]
+    fn wraps_words_at_max_width() {
+        assert_eq!(
+            lines("hello big world", Some(40.0)),
+            ["hello", "big", "world"]
+        );
+        assert_eq!(lines("hello big world", Some(45.0)), ["hello big", "world"]);
+    }
+
+    #[test]
+    fn breaks_over_long_words_between_characters() {
+        assert_eq!(lines("abcdefghij", Some(20.0)), ["abcd", "efgh", "ij"]);
+        assert_eq!(lines("ab abcdefgh", Some(20.0)), ["ab", "abcd", "efgh"]);
+    }
+
+    #[test]
+    fn starts_new_lines_on_explicit_newlines() {
+        assert_eq!(lines("ab\ncd", None), ["ab", "cd"]);
+        assert_eq!(lines("ab\n\ncd\n", None), ["ab", "", "cd", ""]);
+        assert_eq!(lines("ab cd\nef", Some(100.0)), ["ab cd", "ef"]);
+    }
+
+    #[test]
+    fn keeps_a_single_empty_line_for_empty_text() {
+        assert_eq!(lines("", None), [""]);
+        assert_eq!(lines("", Some(10.0)), [""]);
+    }
+}
@@ -0,0 +1,101 @@
+use super::layout::{layout, Run};
+use super::{Font, TextStyle};
+
+/// Bounding box of a single glyph, relative to the position the text is drawn at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphBounds {
+    /// X-coordinate of the left edge of the glyph.
+    pub x: f32,
+    /// Y-coordinate of the top edge of the glyph.
+    pub y: f32,
+    /// Width of the glyph.
+    pub width: f32,
+    /// Height of the glyph.
+    pub height: f32,
+}
+
+/// Measured size of a piece of text.
+///
+/// # Fields
+///
+/// - `width`: Advance width of the widest line.
+/// - `height`: Height from the top of the first line to the bottom of the last line.
+/// - `ascent`: Distance from the baseline to the top of a line.
+/// - `descent`: Distance from the baseline to the bottom of a line, usually negative.
+/// - `glyphs`: Bounding box of every laid out glyph, in the order of the text.
+///   Glyphs without an outline, such as spaces, have an empty box on the baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMetrics {
+    /// Advance width of the widest line.
+    pub width: f32,
+    /// Height from the top of the first line to the bottom of the last line.
+    pub height: f32,
+    /// Distance from the baseline to the top of a line.
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of a line.
+    pub descent: f32,
+    /// Bounding box of every laid out glyph.
+    pub glyphs: Vec<GlyphBounds>,
+}
+
+/// Measures `text` as it would be drawn at `(0, 0)` with the given size and style.
+///
+/// The measurement uses the same layout as text components, so wrapping, line spacing and
+/// alignment of the style are taken into account.
+///
+/// # Examples
+///
+/// ```
+/// use omage::text::{measure_text, Font, TextStyle};
+///
+/// let font = Font::from_file("./fonts/Roboto-Medium.ttf").unwrap();
+/// let metrics = measure_text(&font, 32, "Hello, Rust!", &TextStyle::default());
+///
+/// assert!(metrics.width > 0.0);
+/// assert_eq!(metrics.glyphs.len(), 12);
+/// ```
+pub fn measure_text(font: &Font, size: u32, text: &str, style: &TextStyle) -> TextMetrics {
+    let run = Run {
+        font,
+        size,
+        text: text.to_string(),
+        bold: style.bold,
+        italic: style.italic,
+    };
+    let v_metrics = run.v_metrics();
+    let layout = layout(std::slice::from_ref(&run), (0.0, 0.0), style);
+
+    let width = layout
+        .lines
+        .iter()
+        .map(|line| line.width)
+        .fold(0.0, f32::max);
+
+    let glyphs = layout
+        .lines
+        .iter()
+        .flat_map(|line| line.glyphs.iter())
+        .map(|glyph| match run.bounds(glyph.id) {
+            Some(bounds) => GlyphBounds {
+                x: glyph.position.x + bounds.min.x,
+                y: glyph.position.y + bounds.min.y,
+                width: bounds.width(),
+                height: bounds.height(),
+            },
+            None => GlyphBounds {
+                x: glyph.position.x,
+                y: glyph.position.y,
+                width: 0.0,
+                height: 0.0,
+            },
+        })
+        .collect();
+
+    TextMetrics {
+        width,
+        height: layout.height,
+        ascent: v_metrics.ascent,
+        descent: v_metrics.descent,
+        glyphs,
+    }
+}
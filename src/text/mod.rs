@@ -0,0 +1,49 @@
+//! Text styling and layout.
+//!
+//! The `text` module holds the types used to control how `Text` components are laid out,
+//! such as line wrapping, line spacing, alignment and shrinking text to fit a box, the
+//! `Span` type for rich text, and `measure_text` to know how large a piece of text will be
+//! before drawing it.
+//!
+//! # Examples
+//!
+//! ```
+//! use omage::text::TextStyle;
+//! use omage::{Components, Rgba};
+//!
+//! // Wrap the paragraph at 200 pixels and space the lines out a little.
+//! let style = TextStyle {
+//!     max_width: Some(200.0),
+//!     line_height: 1.2,
+//!     ..Default::default()
+//! };
+//!
+//! let text = Components::StyledText(
+//!     10.0,
+//!     10.0,
+//!     24,
+//!     "Hello, Rust!\nThis line is long enough to be wrapped.",
+//!     Rgba([0, 0, 0, 255]),
+//!     style,
+//! );
+//! ```
+
+mod bitmap;
+pub(crate) mod fit;
+mod font;
+pub(crate) mod layout;
+mod metrics;
+mod outline;
+#[cfg(feature = "shaping")]
+mod shaping;
+mod span;
+mod style;
+pub(crate) mod text_path;
+
+pub use font::Font;
+pub use metrics::{measure_text, GlyphBounds, TextMetrics};
+pub use span::Span;
+pub use style::{
+    Highlight, HorizontalAlign, LineJoin, Outline, Shadow, TextFit, TextPath, TextStyle,
+    VerticalAlign,
+};
@@ -0,0 +1,265 @@
+use owned_ttf_parser::{AsFaceRef, FaceMut, OwnedFace};
+use rusttype::{point, GlyphId, OutlineBuilder, Rect, VMetrics};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A glyph drawn from a color image instead of being filled with the color of its span.
+#[derive(Clone)]
+pub(crate) struct ColorGlyph {
+    /// The image of the glyph, scaled to the size it is drawn at.
+    pub image: Arc<image::RgbaImage>,
+    /// X-offset of the left edge of the image from the origin of the glyph.
+    pub x: f32,
+    /// Y-offset of the top edge of the image from the baseline, negative values being above it.
+    pub y: f32,
+}
+
+/// A TrueType or OpenType font, parsed once when it is loaded.
+///
+/// Like `rusttype`, the size of the font is the distance from its descent to its ascent,
+/// measured before any variation axes are applied so that varying the font does not scale
+/// its glyphs.
+pub(crate) struct OutlineFont {
+    /// The parsed font, which owns the font data, with its variation axes applied.
+    face: OwnedFace,
+    /// Values of the variation axes of the font.
+    variations: Vec<(ttf_parser::Tag, f32)>,
+    /// Distance from the descent to the ascent of the font in font units, without its
+    /// variation axes applied.
+    height: f32,
+    /// Whether the font has `CBDT` or `sbix` tables, which hold images of color glyphs.
+    has_color_glyphs: bool,
+    /// Color images already decoded, by glyph ID and the number of pixels per em they are
+    /// scaled to, `None` for glyphs without a color image.
+    color_glyphs: Mutex<HashMap<(u16, u32), Option<ColorGlyph>>>,
+}
+
+impl OutlineFont {
+    /// Parses a font from the bytes of a TrueType or OpenType file and sets its variation
+    /// axes. Axes that the font does not define are ignored.
+    ///
+    /// Returns `None` if the bytes do not contain a valid font.
+    pub fn new(data: Vec<u8>, variations: Vec<(ttf_parser::Tag, f32)>) -> Option<Self> {
+        let mut face = OwnedFace::from_vec(data, 0).ok()?;
+        let unvaried = face.as_face_ref();
+        let height = unvaried.ascender() as f32 - unvaried.descender() as f32;
+        if height <= 0.0 {
+            return None;
+        }
+        let tables = unvaried.tables();
+        let has_color_glyphs = tables.cbdt.is_some() || tables.sbix.is_some();
+
+        for &(tag, value) in &variations {
+            face.set_variation(tag, value);
+        }
+        Some(Self {
+            face,
+            variations,
+            height,
+            has_color_glyphs,
+            color_glyphs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns a copy of the font with a variation axis set to `value`.
+    pub fn with_variation(&self, tag: ttf_parser::Tag, value: f32) -> Self {
+        let mut variations = self.variations.clone();
+        variations.retain(|&(t, _)| t != tag);
+        variations.push((tag, value));
+        Self::new(self.face.as_slice().to_vec(), variations)
+            .expect("the font has been parsed before")
+    }
+
+    /// Returns the parsed font, with its variation axes applied.
+    pub fn face(&self) -> &ttf_parser::Face<'_> {
+        self.face.as_face_ref()
+    }
+
+    /// Returns the factor converting font units to pixels at the given size.
+    pub fn units_factor(&self, size: f32) -> f32 {
+        size / self.height
+    }
+
+    /// Returns the ID of the glyph drawn for `c`, the missing glyph if there is none.
+    pub fn glyph_id(&self, c: char) -> GlyphId {
+        GlyphId(self.face().glyph_index(c).map_or(0, |id| id.0))
+    }
+
+    /// Returns the vertical metrics of the font at the given size, which follow the
+    /// variation axes of fonts with a metrics variations (`MVAR`) table.
+    pub fn v_metrics(&self, size: f32) -> VMetrics {
+        let factor = self.units_factor(size);
+        let face = self.face();
+        VMetrics {
+            ascent: face.ascender() as f32 * factor,
+            descent: face.descender() as f32 * factor,
+            line_gap: face.line_gap() as f32 * factor,
+        }
+    }
+
+    /// Returns the offset of the top of an underline from the baseline, positive values
+    /// being below it, and its thickness at the given size, read from the `post` table.
+    pub fn underline_metrics(&self, size: f32) -> Option<(f32, f32)> {
+        self.line_metrics(self.face().underline_metrics()?, size)
+    }
+
+    /// Returns the offset of the top of a strikethrough line from the baseline, positive
+    /// values being below it, and its thickness at the given size, read from the `OS/2`
+    /// table.
+    pub fn strikeout_metrics(&self, size: f32) -> Option<(f32, f32)> {
+        self.line_metrics(self.face().strikeout_metrics()?, size)
+    }
+
+    /// Scales the metrics of a line to the given size, or returns `None` if the line has
+    /// no thickness.
+    fn line_metrics(&self, metrics: ttf_parser::LineMetrics, size: f32) -> Option<(f32, f32)> {
+        if metrics.thickness <= 0 {
+            return None;
+        }
+        let factor = self.units_factor(size);
+        Some((
+            -metrics.position as f32 * factor,
+            metrics.thickness as f32 * factor,
+        ))
+    }
+
+    /// Returns the advance width of a glyph at the given size.
+    pub fn advance(&self, id: GlyphId, size: f32) -> f32 {
+        let advance = self.face().glyph_hor_advance(ttf_parser::GlyphId(id.0));
+        advance.unwrap_or(0) as f32 * self.units_factor(size)
+    }
+
+    /// Returns the kerning between two glyphs at the given size, read from the first
+    /// horizontal subtable of the `kern` table that has a value for the pair.
+    pub fn kerning(&self, size: f32, first: GlyphId, second: GlyphId) -> f32 {
+        let (first, second) = (ttf_parser::GlyphId(first.0), ttf_parser::GlyphId(second.0));
+        let kerning = self.face().tables().kern.and_then(|kern| {
+            kern.subtables
+                .into_iter()
+                .filter(|subtable| subtable.horizontal && !subtable.variable)
+                .find_map(|subtable| subtable.glyphs_kerning(first, second))
+        });
+        kerning.unwrap_or(0) as f32 * self.units_factor(size)
+    }
+
+    /// Returns the exact bounding box of the outline of a glyph at the given size, relative
+    /// to its origin, or `None` if the glyph has no outline.
+    pub fn bounds(&self, id: GlyphId, size: f32) -> Option<Rect<f32>> {
+        let factor = self.units_factor(size);
+        let mut outline = ScaledOutline {
+            builder: &mut Discard,
+            factor,
+        };
+        let bounds = self
+            .face()
+            .outline_glyph(ttf_parser::GlyphId(id.0), &mut outline)?;
+        Some(Rect {
+            min: point(bounds.x_min as f32 * factor, -bounds.y_max as f32 * factor),
+            max: point(bounds.x_max as f32 * factor, -bounds.y_min as f32 * factor),
+        })
+    }
+
+    /// Builds the outline of a glyph at the given size, with its origin on the baseline.
+    pub fn build_outline(&self, id: GlyphId, size: f32, builder: &mut impl OutlineBuilder) {
+        let factor = self.units_factor(size);
+        let mut outline = ScaledOutline { builder, factor };
+        self.face()
+            .outline_glyph(ttf_parser::GlyphId(id.0), &mut outline);
+    }
+
+    /// Returns the color image of a glyph at the given size, or `None` if the glyph has none.
+    ///
+    /// Color images are read from the `CBDT` and `sbix` tables used by color emoji fonts,
+    /// picking the strike closest to the size and scaling it to fit. Every image is decoded
+    /// and scaled once for each size it is drawn at.
+    pub fn color_glyph(&self, id: GlyphId, size: f32) -> Option<ColorGlyph> {
+        if !self.has_color_glyphs {
+            return None;
+        }
+        let pixels_per_em = self.units_factor(size) * self.face().units_per_em() as f32;
+        let mut color_glyphs = self
+            .color_glyphs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        color_glyphs
+            .entry((id.0, pixels_per_em.to_bits()))
+            .or_insert_with(|| self.decode_color_glyph(id, pixels_per_em))
+            .clone()
+    }
+
+    /// Decodes the color image of a glyph and scales it to `pixels_per_em`.
+    fn decode_color_glyph(&self, id: GlyphId, pixels_per_em: f32) -> Option<ColorGlyph> {
+        let raster = self.face().glyph_raster_image(
+            ttf_parser::GlyphId(id.0),
+            pixels_per_em.round().clamp(1.0, u16::MAX as f32) as u16,
+        )?;
+        let image = image::load_from_memory_with_format(raster.data, image::ImageFormat::Png)
+            .ok()?
+            .to_rgba8();
+
+        let factor = pixels_per_em / raster.pixels_per_em as f32;
+        let width = (image.width() as f32 * factor).round().max(1.0) as u32;
+        let height = (image.height() as f32 * factor).round().max(1.0) as u32;
+        Some(ColorGlyph {
+            image: Arc::new(image::imageops::resize(
+                &image,
+                width,
+                height,
+                image::imageops::FilterType::Triangle,
+            )),
+            x: raster.x as f32 * factor,
+            y: -(raster.y as f32 + raster.height as f32) * factor,
+        })
+    }
+}
+
+/// Forwards an outline in font units to an outline builder in pixels, flipping the y-axis
+/// so that it points down.
+struct ScaledOutline<'a, B> {
+    /// The builder receiving the scaled outline.
+    builder: &'a mut B,
+    /// Factor converting font units to pixels.
+    factor: f32,
+}
+
+impl<B: OutlineBuilder> ttf_parser::OutlineBuilder for ScaledOutline<'_, B> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let f = self.factor;
+        self.builder.move_to(x * f, -y * f);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let f = self.factor;
+        self.builder.line_to(x * f, -y * f);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let f = self.factor;
+        self.builder.quad_to(x1 * f, -y1 * f, x * f, -y * f);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let f = self.factor;
+        self.builder
+            .curve_to(x1 * f, -y1 * f, x2 * f, -y2 * f, x * f, -y * f);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
+
+/// An outline builder that ignores the outline, for when only the bounds are needed.
+struct Discard;
+
+impl OutlineBuilder for Discard {
+    fn move_to(&mut self, _: f32, _: f32) {}
+
+    fn line_to(&mut self, _: f32, _: f32) {}
+
+    fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {}
+
+    fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+
+    fn close(&mut self) {}
+}
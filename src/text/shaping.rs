@@ -0,0 +1,210 @@
+use super::font::FontKind;
+use super::layout::{Glyph, Item, Run};
+use super::TextStyle;
+use rusttype::{point, GlyphId};
+use rustybuzz::{Direction, Feature, Tag, UnicodeBuffer};
+use std::ops::Range;
+use unicode_bidi::BidiInfo;
+
+/// The whole text to lay out, with the bidirectional levels of every paragraph resolved
+/// once, before it is broken into lines.
+pub(crate) struct BidiText<'t> {
+    /// Bidirectional levels of the text.
+    info: BidiInfo<'t>,
+    /// Byte offset of every item in the text, followed by the length of the text.
+    offsets: Vec<usize>,
+}
+
+impl<'t> BidiText<'t> {
+    /// Resolves the bidirectional levels of every paragraph of `text`, the characters of
+    /// all items in order.
+    pub fn new(text: &'t str) -> Self {
+        let offsets = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .collect();
+        Self {
+            info: BidiInfo::new(text, None),
+            offsets,
+        }
+    }
+}
+
+/// Lays out the line made of the items in `line` on a baseline at `y = 0`, starting at
+/// `x = 0`, with bidirectional reordering and glyph shaping.
+///
+/// The line is split into directional runs, which are put in visual order using the levels
+/// of its paragraph, and every part of a directional run that uses a single font is shaped
+/// on its own. Letter spacing and word spacing of the style are applied between clusters,
+/// while kerning and tabular figures are enabled or disabled through the matching OpenType
+/// features.
+///
+/// Returns the positioned glyphs and the advance width of the line.
+pub(crate) fn shape_line(
+    runs: &[Run],
+    items: &[Item],
+    line: Range<usize>,
+    bidi: &BidiText,
+    style: &TextStyle,
+) -> (Vec<Glyph>, f32) {
+    let text = bidi.info.text;
+    let starts = &bidi.offsets;
+    let item_at = |byte: usize| starts.partition_point(|&start| start < byte);
+
+    let features = [
+        Feature::new(Tag::from_bytes(b"kern"), style.kerning as u32, ..),
+        Feature::new(Tag::from_bytes(b"tnum"), style.tabular_figures as u32, ..),
+    ];
+
+    let mut glyphs = Vec::new();
+    let mut caret = 0.0;
+    if line.is_empty() {
+        return (glyphs, caret);
+    }
+
+    // Lines never span a newline, so they lie within a single paragraph.
+    let bytes = starts[line.start]..starts[line.end];
+    let paragraph = bidi
+        .info
+        .paragraphs
+        .iter()
+        .find(|paragraph| paragraph.range.contains(&bytes.start))
+        .expect("a line lies within a paragraph");
+    let (levels, visual_runs) = bidi.info.visual_runs(paragraph, bytes);
+
+    // Item of the last shaped cluster, kept across parts so that letter spacing is also
+    // added where the font or the direction changes.
+    let mut last_item = None;
+    for visual_run in visual_runs {
+        let rtl = levels[visual_run.start].is_rtl();
+        let (start, end) = (item_at(visual_run.start), item_at(visual_run.end));
+
+        // Split the directional run into parts drawn with a single font.
+        let mut parts = Vec::new();
+        let mut part_start = start;
+        for i in start + 1..=end {
+            if i == end || items[i].1 != items[part_start].1 {
+                parts.push(part_start..i);
+                part_start = i;
+            }
+        }
+        if rtl {
+            parts.reverse();
+        }
+
+        for part in parts {
+            let run = &runs[items[part.start].1];
+            let font = match &run.font.inner {
+                FontKind::Outline(font) => font,
+                FontKind::Bitmap(_) => continue,
+            };
+            // The face of the font already has its variation axes applied.
+            let face = match rustybuzz::Face::from_face(font.face().clone()) {
+                Some(face) => face,
+                None => continue,
+            };
+            let factor = font.units_factor(run.size as f32);
+
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(&text[starts[part.start]..starts[part.end]]);
+            buffer.set_direction(if rtl {
+                Direction::RightToLeft
+            } else {
+                Direction::LeftToRight
+            });
+            let shaped = rustybuzz::shape(&face, &features, buffer);
+
+            for (info, position) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                let item = item_at(starts[part.start] + info.cluster as usize);
+                if last_item.is_some() && last_item != Some(item) {
+                    caret += style.letter_spacing;
+                }
+                last_item = Some(item);
+
+                let mut advance = position.x_advance as f32 * factor;
+                if position.x_advance != 0 {
+                    advance += 2.0 * run.embolden() as f32;
+                }
+                if items[item].0 == ' ' {
+                    advance += style.word_spacing;
+                }
+
+                glyphs.push(Glyph {
+                    id: GlyphId(info.glyph_id as u16),
+                    position: point(
+                        caret + position.x_offset as f32 * factor,
+                        -position.y_offset as f32 * factor,
+                    ),
+                    run: items[item].1,
+                    advance,
+                });
+                caret += advance;
+            }
+        }
+    }
+
+    (glyphs, caret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::Font;
+
+    #[test]
+    fn adds_letter_spacing_between_parts() {
+        let font = Font::from_file("./fonts/Roboto-Medium.ttf").unwrap();
+        let runs: Vec<Run> = ["ab", "cd"]
+            .iter()
+            .map(|text| Run {
+                font: &font,
+                size: 20,
+                text: text.to_string(),
+                bold: false,
+                italic: false,
+            })
+            .collect();
+        let items: Vec<Item> = [('a', 0), ('b', 0), ('c', 1), ('d', 1)].to_vec();
+        let bidi = BidiText::new("abcd");
+        let width = |letter_spacing| {
+            let style = TextStyle {
+                letter_spacing,
+                kerning: false,
+                ..TextStyle::default()
+            };
+            shape_line(&runs, &items, 0..items.len(), &bidi, &style).1
+        };
+        assert!((width(10.0) - width(0.0) - 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn reorders_lines_with_the_levels_of_their_paragraph() {
+        let font = Font::from_file("./fonts/Roboto-Medium.ttf").unwrap();
+        let runs: Vec<Run> = ["ab\n\u{5d0}\u{5d1}", "\u{5d2}\u{5d3}"]
+            .iter()
+            .map(|text| Run {
+                font: &font,
+                size: 20,
+                text: text.to_string(),
+                bold: false,
+                italic: false,
+            })
+            .collect();
+        let items: Vec<Item> = runs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, run)| run.text.chars().map(move |c| (c, i)))
+            .collect();
+        let text: String = items.iter().map(|&(c, _)| c).collect();
+        let bidi = BidiText::new(&text);
+
+        let line = |range| {
+            let (glyphs, _) = shape_line(&runs, &items, range, &bidi, &TextStyle::default());
+            glyphs.iter().map(|glyph| glyph.run).collect::<Vec<_>>()
+        };
+        assert_eq!(line(0..2), [0, 0]);
+        assert_eq!(line(3..7), [1, 1, 0, 0]);
+        assert_eq!(line(4..6), [1, 0]);
+    }
+}
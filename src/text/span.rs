@@ -0,0 +1,74 @@
+use image::Rgba;
+
+/// A piece of rich text with its own font, size, color and decorations.
+///
+/// Spans are laid out one after another on shared baselines, so a single text component can
+/// mix several styles.
+///
+/// # Examples
+///
+/// ```
+/// use omage::colors::*;
+/// use omage::text::{Span, TextStyle};
+/// use omage::Components;
+///
+/// let spans = vec![
+///     Span::new("Price: ", 32, BLACK),
+///     Span {
+///         underline: true,
+///         ..Span::new("$9.99", 40, RED)
+///     },
+/// ];
+///
+/// let text = Components::RichText(10.0, 10.0, spans, TextStyle::default());
+/// ```
+///
+/// # Fields
+///
+/// - `text`: Text content of the span.
+/// - `font_path`: Optional path to the font file of the span. The font of the `Config` is used if it is `None`.
+/// - `size`: Font size of the span.
+/// - `color`: Color of the span in Rgba format.
+/// - `underline`: Whether the span is underlined.
+/// - `strikethrough`: Whether the span is struck through.
+/// - `bold`: Whether the glyphs of the span are synthetically emboldened.
+/// - `italic`: Whether the glyphs of the span are synthetically slanted.
+/// - `variations`: Values of variation axes of a variable font, such as `("wght", 700.0)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    /// Text content of the span.
+    pub text: &'static str,
+    /// Path to the font file of the span.
+    pub font_path: Option<&'static str>,
+    /// Font size of the span.
+    pub size: u32,
+    /// Color of the span in Rgba format.
+    pub color: Rgba<u8>,
+    /// Whether the span is underlined.
+    pub underline: bool,
+    /// Whether the span is struck through.
+    pub strikethrough: bool,
+    /// Whether the glyphs of the span are synthetically emboldened.
+    pub bold: bool,
+    /// Whether the glyphs of the span are synthetically slanted.
+    pub italic: bool,
+    /// Values of variation axes of a variable font.
+    pub variations: &'static [(&'static str, f32)],
+}
+
+impl Span {
+    /// Creates a new span drawn with the font of the `Config`, without decorations and emphasis.
+    pub fn new(text: &'static str, size: u32, color: Rgba<u8>) -> Self {
+        Self {
+            text,
+            font_path: None,
+            size,
+            color,
+            underline: false,
+            strikethrough: false,
+            bold: false,
+            italic: false,
+            variations: &[],
+        }
+    }
+}
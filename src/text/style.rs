@@ -0,0 +1,206 @@
+use image::Rgba;
+
+/// Horizontal alignment of text relative to its anchor point or bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HorizontalAlign {
+    /// Lines start at the anchor, or at the left edge of the bounding box.
+    #[default]
+    Left,
+    /// Lines are centered on the anchor, or in the bounding box.
+    Center,
+    /// Lines end at the anchor, or at the right edge of the bounding box.
+    Right,
+}
+
+/// Vertical alignment of text relative to its anchor point or bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlign {
+    /// The top of the first line is placed at the anchor, or at the top edge of the bounding box.
+    #[default]
+    Top,
+    /// The text is centered vertically on the anchor, or in the bounding box.
+    Middle,
+    /// The baseline of the first line is placed at the anchor.
+    ///
+    /// In a bounding box, the baseline of the last line is placed on the bottom edge.
+    Baseline,
+    /// The bottom of the last line is placed at the anchor, or at the bottom edge of the bounding box.
+    Bottom,
+}
+
+/// Shape of the corners of a text outline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Corners are rounded.
+    #[default]
+    Round,
+    /// Corners are kept sharp.
+    Miter,
+    /// Corners are cut off.
+    Bevel,
+}
+
+/// An outline drawn around the shapes of the glyphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    /// Color of the outline in Rgba format.
+    pub color: Rgba<u8>,
+    /// Width of the outline in pixels, scaled along with the text by its transform.
+    pub width: f32,
+    /// Shape of the corners of the outline.
+    pub join: LineJoin,
+}
+
+/// A drop shadow drawn beneath the text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// Offset (X, Y) of the shadow from the text in pixels, turned and scaled along with
+    /// the text by its transform.
+    pub offset: (f32, f32),
+    /// Color of the shadow in Rgba format.
+    pub color: Rgba<u8>,
+    /// Radius of the blur applied to the shadow in pixels, `0` for a hard shadow.
+    pub blur: u32,
+}
+
+/// A box drawn behind the text, e.g. to make it stand out as a callout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlight {
+    /// Color of the box in Rgba format.
+    pub color: Rgba<u8>,
+    /// Space in pixels between the text and the edges of the box.
+    pub padding: f32,
+    /// Radius of the corners of the box in pixels, `0.0` for square corners.
+    pub corner_radius: f32,
+}
+
+/// A path that text is laid out along instead of a straight line.
+///
+/// Coordinates of the path are relative to the text position. Each glyph is placed on the
+/// path at the middle of its advance and rotated to follow the tangent of the path there.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextPath {
+    /// A circle centered on the text position, followed clockwise.
+    Arc {
+        /// Radius of the circle.
+        radius: f32,
+        /// Clockwise angle in degrees at which the path starts, `-90.0` being the top.
+        start_angle: f32,
+    },
+    /// A polyline through the given points.
+    Polyline(Vec<(f32, f32)>),
+    /// A cubic Bézier curve from the first to the last point, with two control points.
+    Bezier((f32, f32), (f32, f32), (f32, f32), (f32, f32)),
+}
+
+/// Options for shrinking text until it fits in its bounding box.
+///
+/// The size of the text component is used as the largest size to try, and the text is
+/// drawn at the largest size between `min_size` and that size at which it fits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextFit {
+    /// Smallest font size to try.
+    pub min_size: u32,
+    /// Whether lines are wrapped at the width of the bounding box.
+    pub wrap: bool,
+    /// Whether the text is truncated with an ellipsis if it overflows at `min_size`.
+    pub ellipsis: bool,
+}
+
+/// Styling options for a text component.
+///
+/// `TextStyle` implements `Default`, so only the options you care about need to be set.
+///
+/// # Fields
+///
+/// - `max_width`: Optional maximum width of a line in pixels. Words that would overflow are wrapped.
+/// - `line_height`: Line height as a multiple of the font's natural line height.
+/// - `outline`: Optional outline drawn around the glyphs.
+/// - `shadow`: Optional drop shadow drawn beneath the text and its outline.
+/// - `highlight`: Optional box drawn behind the whole text, rotated along with it. Ignored for
+///   text laid out along a path.
+/// - `underline`: Whether every span is underlined.
+/// - `strikethrough`: Whether every span is struck through.
+/// - `bold`: Whether every span is synthetically emboldened, by growing the glyphs.
+/// - `italic`: Whether every span is synthetically slanted, by skewing the glyphs.
+/// - `align`: Horizontal alignment of each line.
+/// - `vertical_align`: Vertical alignment of the whole text.
+/// - `bounds`: Optional size of a bounding box whose top-left corner is the text position.
+///   Without a bounding box, the text position is used as the anchor point for alignment.
+/// - `rotation`: Clockwise rotation of the text in degrees around the text position.
+/// - `path`: Optional path the text is laid out along. The horizontal alignment places the
+///   text at the start, middle or end of the path, and the vertical alignment is relative to
+///   the path, so `VerticalAlign::Baseline` puts the baseline on it. `rotation` is ignored.
+/// - `letter_spacing`: Extra space in pixels between neighbouring glyphs, negative to tighten.
+/// - `word_spacing`: Extra space in pixels added to every space character.
+/// - `kerning`: Whether the kerning of the font is applied.
+/// - `tabular_figures`: Whether digits are given the same width, so that numbers line up.
+/// - `fit`: Optional options for shrinking the text until it fits in the bounding box.
+///   Ignored if there is no bounding box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    /// Maximum width of a line in pixels.
+    pub max_width: Option<f32>,
+    /// Line height as a multiple of the font's natural line height.
+    pub line_height: f32,
+    /// Outline drawn around the glyphs.
+    pub outline: Option<Outline>,
+    /// Drop shadow drawn beneath the text.
+    pub shadow: Option<Shadow>,
+    /// Box drawn behind the text.
+    pub highlight: Option<Highlight>,
+    /// Whether every span is underlined.
+    pub underline: bool,
+    /// Whether every span is struck through.
+    pub strikethrough: bool,
+    /// Whether every span is synthetically emboldened.
+    pub bold: bool,
+    /// Whether every span is synthetically slanted.
+    pub italic: bool,
+    /// Horizontal alignment of each line.
+    pub align: HorizontalAlign,
+    /// Vertical alignment of the whole text.
+    pub vertical_align: VerticalAlign,
+    /// Bounding box (Width, Height) to align the text in.
+    pub bounds: Option<(f32, f32)>,
+    /// Shrinks the text to fit in the bounding box.
+    pub fit: Option<TextFit>,
+    /// Clockwise rotation of the text in degrees around the text position.
+    pub rotation: f32,
+    /// Path the text is laid out along.
+    pub path: Option<TextPath>,
+    /// Extra space in pixels between neighbouring glyphs.
+    pub letter_spacing: f32,
+    /// Extra space in pixels added to every space character.
+    pub word_spacing: f32,
+    /// Whether the kerning of the font is applied.
+    pub kerning: bool,
+    /// Whether digits are given the same width.
+    pub tabular_figures: bool,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            line_height: 1.0,
+            outline: None,
+            shadow: None,
+            highlight: None,
+            underline: false,
+            strikethrough: false,
+            bold: false,
+            italic: false,
+            align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            bounds: None,
+            fit: None,
+            rotation: 0.0,
+            path: None,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            kerning: true,
+            tabular_figures: false,
+        }
+    }
+}
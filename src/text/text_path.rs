@@ -0,0 +1,85 @@
+use super::TextPath;
+
+/// Number of segments used to approximate a full circle or a Bézier curve.
+const SEGMENTS: usize = 256;
+
+/// A path flattened into a polyline, used to place glyphs along a `TextPath`.
+pub(crate) struct Polyline {
+    /// Points of the polyline.
+    points: Vec<(f32, f32)>,
+    /// Distance along the polyline at each of its points.
+    distances: Vec<f32>,
+}
+
+impl Polyline {
+    /// Flattens a text path whose coordinates are relative to `origin`.
+    pub fn new(path: &TextPath, origin: (f32, f32)) -> Self {
+        let points: Vec<(f32, f32)> = match path {
+            TextPath::Arc {
+                radius,
+                start_angle,
+            } => (0..=SEGMENTS)
+                .map(|i| {
+                    let angle = (start_angle + 360.0 * i as f32 / SEGMENTS as f32).to_radians();
+                    (radius * angle.cos(), radius * angle.sin())
+                })
+                .collect(),
+            TextPath::Polyline(points) => points.clone(),
+            TextPath::Bezier(p0, p1, p2, p3) => (0..=SEGMENTS)
+                .map(|i| {
+                    let t = i as f32 / SEGMENTS as f32;
+                    let u = 1.0 - t;
+                    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                    (
+                        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+                    )
+                })
+                .collect(),
+        };
+        let points: Vec<(f32, f32)> = points
+            .iter()
+            .map(|p| (origin.0 + p.0, origin.1 + p.1))
+            .collect();
+
+        let mut distances = vec![0.0];
+        for pair in points.windows(2) {
+            let (dx, dy) = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+            distances.push(distances[distances.len() - 1] + (dx * dx + dy * dy).sqrt());
+        }
+
+        Self { points, distances }
+    }
+
+    /// Returns the length of the polyline.
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the point at `distance` along the polyline and the clockwise angle of the
+    /// tangent there in degrees.
+    ///
+    /// Distances before the start or past the end extend the first or last segment.
+    pub fn at(&self, distance: f32) -> ((f32, f32), f32) {
+        if self.points.len() < 2 {
+            return (self.points.first().copied().unwrap_or((0.0, 0.0)), 0.0);
+        }
+
+        let segment = self
+            .distances
+            .windows(2)
+            .position(|d| distance < d[1])
+            .unwrap_or(self.points.len() - 2);
+        let (p0, p1) = (self.points[segment], self.points[segment + 1]);
+        let length = self.distances[segment + 1] - self.distances[segment];
+        let t = if length > 0.0 {
+            (distance - self.distances[segment]) / length
+        } else {
+            0.0
+        };
+
+        let point = (p0.0 + (p1.0 - p0.0) * t, p0.1 + (p1.1 - p0.1) * t);
+        let angle = (p1.1 - p0.1).atan2(p1.0 - p0.0).to_degrees();
+        (point, angle)
+    }
+}
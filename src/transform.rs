@@ -0,0 +1,200 @@
+/// A 2D affine transform, used to move, rotate, scale and skew components.
+///
+/// A point `(x, y)` is mapped to `(a * x + c * y + e, b * x + d * y + f)`. Transforms are
+/// combined with `then`, and rotations, scales and skews are around the origin of the
+/// canvas unless stated otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use omage::{Components, Rgba, Transform};
+///
+/// // A rectangle rotated by 30 degrees around its center.
+/// let rectangle = Components::Rectangle(40.0, 60.0, 20.0, 30.0, Rgba([255, 0, 0, 255]))
+///     .transform(Transform::rotate_around(30.0, 50.0, 50.0));
+///
+/// // An ellipse, from a circle stretched horizontally around its center.
+/// let ellipse = Components::Circle(50.0, 50.0, 20.0, Rgba([0, 0, 255, 255])).transform(
+///     Transform::translate(-50.0, -50.0)
+///         .then(&Transform::scale(2.0, 1.0))
+///         .then(&Transform::translate(50.0, 50.0)),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+impl Transform {
+    /// Creates a transform that leaves points unchanged.
+    pub fn identity() -> Self {
+        Self {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            e: 0.0,
+            f: 0.0,
+        }
+    }
+
+    /// Creates a transform that moves points by `(tx, ty)`.
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Self {
+            e: tx,
+            f: ty,
+            ..Self::identity()
+        }
+    }
+
+    /// Creates a transform that rotates points clockwise by `degrees` around the origin.
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Self::identity()
+        }
+    }
+
+    /// Creates a transform that rotates points clockwise by `degrees` around `(cx, cy)`.
+    pub fn rotate_around(degrees: f32, cx: f32, cy: f32) -> Self {
+        Self::translate(-cx, -cy)
+            .then(&Self::rotate(degrees))
+            .then(&Self::translate(cx, cy))
+    }
+
+    /// Creates a transform that scales points by `sx` horizontally and `sy` vertically.
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            d: sy,
+            ..Self::identity()
+        }
+    }
+
+    /// Creates a transform that skews points horizontally by `x_degrees` and vertically by
+    /// `y_degrees`.
+    ///
+    /// Positive horizontal angles move points below the origin to the right, and positive
+    /// vertical angles move points right of the origin down.
+    pub fn skew(x_degrees: f32, y_degrees: f32) -> Self {
+        Self {
+            b: y_degrees.to_radians().tan(),
+            c: x_degrees.to_radians().tan(),
+            ..Self::identity()
+        }
+    }
+
+    /// Returns a transform that applies `self` first and then `other`.
+    pub fn then(&self, other: &Transform) -> Self {
+        Self {
+            a: other.a * self.a + other.c * self.b,
+            b: other.b * self.a + other.d * self.b,
+            c: other.a * self.c + other.c * self.d,
+            d: other.b * self.c + other.d * self.d,
+            e: other.a * self.e + other.c * self.f + other.e,
+            f: other.b * self.e + other.d * self.f + other.f,
+        }
+    }
+
+    /// Returns the transform that undoes `self`, or `None` if it collapses points onto a line.
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        Some(Self {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    /// Applies the transform to the point `(x, y)`.
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Applies the transform to the vector `(x, y)`, e.g. an offset, leaving out the
+    /// translation.
+    pub(crate) fn apply_vector(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.a * x + self.c * y, self.b * x + self.d * y)
+    }
+
+    /// Returns the factor by which the transform scales lengths on average, e.g. to scale
+    /// the width of a line drawn through it.
+    pub(crate) fn scale_factor(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that two points are equal up to rounding errors.
+    fn assert_close((x, y): (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (x - expected.0).abs() < 1e-4 && (y - expected.1).abs() < 1e-4,
+            "{:?} != {:?}",
+            (x, y),
+            expected
+        );
+    }
+
+    #[test]
+    fn then_applies_the_first_transform_first() {
+        let translate = Transform::translate(10.0, 0.0);
+        let scale = Transform::scale(2.0, 3.0);
+        assert_close(translate.then(&scale).apply(1.0, 1.0), (22.0, 3.0));
+        assert_close(scale.then(&translate).apply(1.0, 1.0), (12.0, 3.0));
+        // Rotations are clockwise on the canvas, whose y-axis points down.
+        assert_close(Transform::rotate(90.0).apply(1.0, 0.0), (0.0, 1.0));
+        assert_close(
+            Transform::rotate_around(90.0, 5.0, 5.0).apply(6.0, 5.0),
+            (5.0, 6.0),
+        );
+    }
+
+    #[test]
+    fn invert_undoes_the_transform() {
+        let transform = Transform::rotate(30.0)
+            .then(&Transform::skew(10.0, 0.0))
+            .then(&Transform::scale(2.0, 0.5))
+            .then(&Transform::translate(7.0, -3.0));
+        let inverse = transform.invert().unwrap();
+        let (x, y) = transform.apply(3.0, 4.0);
+        assert_close(inverse.apply(x, y), (3.0, 4.0));
+        assert_close(transform.then(&inverse).apply(-2.0, 9.0), (-2.0, 9.0));
+        assert!(Transform::scale(1.0, 0.0).invert().is_none());
+    }
+
+    #[test]
+    fn vectors_are_not_translated() {
+        let transform = Transform::rotate(90.0)
+            .then(&Transform::scale(2.0, 2.0))
+            .then(&Transform::translate(5.0, 5.0));
+        assert_close(transform.apply_vector(1.0, 0.0), (0.0, 2.0));
+        assert!((transform.scale_factor() - 2.0).abs() < 1e-4);
+    }
+}